@@ -0,0 +1,141 @@
+use crate::config::MapConfig;
+use crate::zombie_state::direction_to_delta;
+use bevy::prelude::*;
+use bevy_life::Cell;
+use serde::{Deserialize, Serialize};
+
+/// What the edges of the map are.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Boundary {
+    #[default]
+    Wall, // nobody goes past the edge
+    Wrap, // the edges join the opposite ones, the map is a torus
+    Open, // groups can walk off the edge and are gone, counted as emigrated
+}
+
+/// Grid cell with its 8 neighbors worked out once at spawn, across the edges on a wrapping map.
+#[derive(Debug, Clone, Component, Reflect)]
+pub struct GridCell {
+    coords: IVec2,
+    neighbors: Vec<IVec2>,
+}
+
+impl GridCell {
+    pub fn new(coords: IVec2, map: &MapConfig) -> Self {
+        let size = IVec2::new(map.width as i32, map.height as i32);
+        let neighbors = (0..8)
+            .filter_map(|direction| {
+                let neighbor = coords + direction_to_delta(direction)?;
+                if map.boundary == Boundary::Wrap {
+                    Some(neighbor.rem_euclid(size))
+                } else {
+                    (neighbor.cmpge(IVec2::ZERO).all() && neighbor.cmplt(size).all())
+                        .then_some(neighbor)
+                }
+            })
+            .collect();
+        GridCell { coords, neighbors }
+    }
+
+    /// On the border of the map.
+    pub fn on_edge(coords: IVec2, map: &MapConfig) -> bool {
+        coords.x == 0
+            || coords.y == 0
+            || coords.x == map.width as i32 - 1
+            || coords.y == map.height as i32 - 1
+    }
+}
+
+impl Cell for GridCell {
+    type Coordinates = IVec2;
+
+    fn coords(&self) -> &IVec2 {
+        &self.coords
+    }
+
+    fn neighbor_coordinates(&self) -> impl ExactSizeIterator<Item = IVec2> + '_ {
+        self.neighbors.iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zombie_state::{Status, ZombieState};
+    use bevy_life::CellState;
+
+    fn map(boundary: Boundary) -> MapConfig {
+        MapConfig {
+            width: 10,
+            height: 5,
+            boundary,
+            ..MapConfig::default()
+        }
+    }
+
+    fn neighbors(coords: IVec2, map: &MapConfig) -> Vec<IVec2> {
+        GridCell::new(coords, map).neighbor_coordinates().collect()
+    }
+
+    #[test]
+    fn walls_and_open_edges_cut_the_neighbors_off() {
+        for boundary in [Boundary::Wall, Boundary::Open] {
+            let map = map(boundary);
+            assert_eq!(neighbors(IVec2::new(0, 0), &map).len(), 3);
+            assert_eq!(neighbors(IVec2::new(4, 0), &map).len(), 5);
+            assert_eq!(neighbors(IVec2::new(9, 4), &map).len(), 3);
+            assert_eq!(neighbors(IVec2::new(4, 2), &map).len(), 8);
+        }
+    }
+
+    #[test]
+    fn wrapping_edges_join_the_opposite_ones() {
+        let map = map(Boundary::Wrap);
+        let corner = neighbors(IVec2::new(0, 0), &map);
+        assert_eq!(corner.len(), 8);
+        for across in [
+            IVec2::new(9, 4),
+            IVec2::new(9, 0),
+            IVec2::new(0, 4),
+            IVec2::new(1, 4),
+        ] {
+            assert!(corner.contains(&across), "{across} missing from {corner:?}");
+        }
+        assert!(
+            corner
+                .iter()
+                .all(|n| n.cmpge(IVec2::ZERO).all() && n.x < 10 && n.y < 5)
+        );
+    }
+
+    #[test]
+    fn groups_walking_off_an_open_edge_are_counted_as_emigrated() {
+        let map = map(Boundary::Open);
+        let coords = IVec2::new(0, 2);
+        let mut state = ZombieState {
+            xy: coords,
+            status: Status::Human,
+            population: 100,
+            panic_source: 8,
+            open_edge: GridCell::on_edge(coords, &map),
+            ..ZombieState::default()
+        };
+        state.head(&[6], 50); // west, off the map
+        let around: Vec<ZombieState> = neighbors(coords, &map)
+            .into_iter()
+            .map(|xy| {
+                let mut neighbor = ZombieState {
+                    xy,
+                    panic_source: 8,
+                    ..ZombieState::default()
+                };
+                neighbor.stay();
+                neighbor
+            })
+            .collect();
+
+        let ledger = state.new_cell_state(around.iter()).ledger;
+        assert_eq!(ledger.emigrated, 50);
+        assert_eq!(ledger.evacuated, 50);
+    }
+}
@@ -0,0 +1,303 @@
+use crate::loading::LoadingState;
+use crate::rng::scramble;
+use crate::zombie_state::{Status, ZombieState};
+use crate::{CELL_SIZE, WorldSeed, quality, terrain};
+use bevy::color::palettes::css::*;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_life::LifeSystemSet;
+
+const CELL_HALF_SIZE: f32 = CELL_SIZE / 2.0;
+
+/// Draws every cell: its terrain, and the humans or zombies on it sized by their numbers.
+pub struct CellViewsPlugin;
+
+impl Plugin for CellViewsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(LoadingState::BuildingViews),
+            (setup_assets, setup_views).chain(),
+        )
+        .add_systems(
+            Update,
+            update_cell_views
+                .run_if(quality::cell_views_due)
+                .after(LifeSystemSet::CellUpdate)
+                .run_if(in_state(LoadingState::Ready)),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct RectMesh(Handle<Mesh>);
+
+#[derive(Resource)]
+struct TerrainMaterials(Vec<Handle<ColorMaterial>>); // by biome, in the order of Biome::ALL
+
+// Terrain views of a biome share one material, these vertex-colored meshes tint it a little per cell
+const TERRAIN_VARIANTS: u64 = 16;
+const TERRAIN_SHADE_RANGE: f32 = 0.12; // how much darker a corner may get
+const TERRAIN_WARMTH_RANGE: f32 = 0.04; // how much redder
+
+#[derive(Resource)]
+struct TerrainMeshes(Vec<Handle<Mesh>>);
+
+#[derive(Resource)]
+struct FeatureMaterials {
+    water: Handle<ColorMaterial>,
+    river: Handle<ColorMaterial>,
+    road: Handle<ColorMaterial>,
+    wall: Handle<ColorMaterial>,
+}
+
+#[derive(Resource)]
+struct ZombieMaterial(Handle<ColorMaterial>);
+
+/// Walkers, runners, tanks and screamers, for the zombie cells where they are the most.
+#[derive(Resource)]
+struct VariantMaterials([Handle<ColorMaterial>; 4]);
+
+#[derive(Resource)]
+struct HumanMaterial(Handle<ColorMaterial>);
+
+#[derive(Resource)]
+struct CampMaterial(Handle<ColorMaterial>);
+
+#[derive(Resource)]
+struct MilitaryMaterial(Handle<ColorMaterial>);
+
+fn setup_assets(
+    seed: Res<WorldSeed>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+) {
+    let rect = Rectangle::from_size(Vec2::splat(1.0));
+    let rect_mesh_handle = meshes.add(rect);
+
+    let terrain_meshes = (0..TERRAIN_VARIANTS)
+        .map(|variant| {
+            // Every corner shaded on its own, blended across the cell
+            let colors: Vec<[f32; 4]> = (0..4)
+                .map(|corner| {
+                    let roll = scramble(seed.0 ^ scramble(variant * 4 + corner));
+                    let shade = 1.0 - (roll % 1000) as f32 / 1000.0 * TERRAIN_SHADE_RANGE;
+                    let warmth = ((roll >> 32) % 1000) as f32 / 1000.0 * TERRAIN_WARMTH_RANGE;
+                    [shade, shade - warmth, shade - 2.0 * warmth, 1.0]
+                })
+                .collect();
+            meshes.add(Mesh::from(rect).with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors))
+        })
+        .collect();
+
+    let terrain_material_handles = terrain::Biome::ALL
+        .map(|biome| {
+            materials.add(Color::from(match biome {
+                terrain::Biome::Plains => TAN,
+                terrain::Biome::Forest => DARK_OLIVEGREEN,
+                terrain::Biome::Desert => SANDY_BROWN,
+                terrain::Biome::Tundra => GAINSBORO,
+                terrain::Biome::Swamp => DARK_KHAKI,
+            }))
+        })
+        .to_vec();
+    let feature_materials = FeatureMaterials {
+        water: materials.add(Color::from(STEEL_BLUE)),
+        river: materials.add(Color::from(CORNFLOWER_BLUE)),
+        road: materials.add(Color::from(DIM_GRAY)),
+        wall: materials.add(Color::from(DARK_SLATE_GRAY)),
+    };
+    let zombie_material_handle = materials.add(Color::from(GREEN));
+    let variant_material_handles = [
+        zombie_material_handle.clone(),
+        materials.add(Color::from(LIME)),
+        materials.add(Color::from(DARK_GREEN)),
+        materials.add(Color::from(YELLOW_GREEN)),
+    ];
+    let human_material_handle = materials.add(Color::from(ROYAL_BLUE));
+    let camp_material_handle = materials.add(Color::from(ORANGE));
+    let military_material_handle = materials.add(Color::from(OLIVE));
+
+    commands.insert_resource(RectMesh(rect_mesh_handle));
+    commands.insert_resource(TerrainMeshes(terrain_meshes));
+
+    commands.insert_resource(TerrainMaterials(terrain_material_handles));
+    commands.insert_resource(feature_materials);
+    commands.insert_resource(ZombieMaterial(zombie_material_handle));
+    commands.insert_resource(VariantMaterials(variant_material_handles));
+    commands.insert_resource(HumanMaterial(human_material_handle));
+    commands.insert_resource(CampMaterial(camp_material_handle));
+    commands.insert_resource(MilitaryMaterial(military_material_handle));
+}
+
+#[derive(Component, Clone, Copy)]
+struct Humans;
+
+#[derive(Component, Clone, Copy)]
+struct Zombies;
+
+/// What the views of a new cell start out with, see setup_assets.
+#[derive(SystemParam)]
+struct NewViewAssets<'w> {
+    rect_mesh: Res<'w, RectMesh>,
+    terrain_meshes: Res<'w, TerrainMeshes>,
+    terrain_materials: Res<'w, TerrainMaterials>,
+    feature_materials: Res<'w, FeatureMaterials>,
+    zombie_material: Res<'w, ZombieMaterial>,
+    human_material: Res<'w, HumanMaterial>,
+}
+
+fn setup_views(
+    cells_q: Query<(Entity, &ZombieState)>,
+    seed: Res<WorldSeed>,
+    mut commands: Commands,
+    assets: NewViewAssets,
+) {
+    let terrain = (
+        Mesh2d(assets.rect_mesh.0.clone()),
+        MeshMaterial2d(assets.terrain_materials.0[0].clone()),
+        Transform {
+            translation: Vec3::new(0.0, 0.0, 1.0),
+            scale: Vec3::new(CELL_SIZE, CELL_SIZE, 1.0),
+            ..default()
+        },
+    );
+
+    let humans = (
+        Mesh2d(assets.rect_mesh.0.clone()),
+        MeshMaterial2d(assets.human_material.0.clone()),
+        Transform {
+            translation: Vec3::new(0.0, 0.0, 3.0), // atop terrain
+            scale: Vec3::new(0.0, 0.0, 1.0),
+            ..default()
+        },
+        Humans,
+    );
+
+    let zombies = (
+        Mesh2d(assets.rect_mesh.0.clone()),
+        MeshMaterial2d(assets.zombie_material.0.clone()),
+        Transform {
+            translation: Vec3::new(0.0, 0.0, 2.0), // atop humans
+            scale: Vec3::new(0.0, 0.0, 1.0),
+            ..default()
+        },
+        Zombies,
+    );
+
+    for (cell, state) in cells_q.iter() {
+        // Same tint for the same cell on every run with this seed
+        let variant = scramble(seed.0 ^ scramble(((state.xy.y as u64) << 32) | state.xy.x as u64))
+            % TERRAIN_VARIANTS;
+        let mut terrain = terrain.clone();
+        terrain.0 = Mesh2d(assets.terrain_meshes.0[variant as usize].clone());
+        terrain.1 = MeshMaterial2d(match state.terrain {
+            terrain::TerrainKind::Land => assets.terrain_materials.0[state.biome as usize].clone(),
+            terrain::TerrainKind::Water => assets.feature_materials.water.clone(),
+            terrain::TerrainKind::River => assets.feature_materials.river.clone(),
+            terrain::TerrainKind::Road => assets.feature_materials.road.clone(),
+            terrain::TerrainKind::Wall => assets.feature_materials.wall.clone(),
+        });
+        commands
+            .entity(cell)
+            .with_child(terrain)
+            .with_child(humans.clone())
+            .with_child(zombies.clone());
+    }
+}
+
+/// Humans view of a cell, scaled and tinted by update_cell_views.
+type HumanViewsQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        &'static mut MeshMaterial2d<ColorMaterial>,
+    ),
+    (With<Humans>, Without<Zombies>),
+>;
+
+/// Zombies view of a cell, tinted by the variant most of them are.
+type ZombieViewsQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        &'static mut MeshMaterial2d<ColorMaterial>,
+    ),
+    (With<Zombies>, Without<Humans>),
+>;
+
+const CELL_MAX_POPULATION: i32 = 1000;
+
+fn update_cell_views(
+    cells_q: Query<(&ZombieState, &Children)>,
+    mut humans_tfs_q: HumanViewsQuery,
+    mut zombies_tfs_q: ZombieViewsQuery,
+    variant_materials: Res<VariantMaterials>,
+    human_material: Res<HumanMaterial>,
+    camp_material: Res<CampMaterial>,
+    military_material: Res<MilitaryMaterial>,
+) {
+    for (state, children) in cells_q.iter() {
+        // Terrain, humans and zombies views, see setup_views
+        let &[_, humans_e, zombies_e, ..] = &children[..] else {
+            continue;
+        };
+        let (Ok((mut humans_tf, mut humans_material)), Ok((mut zombies_tf, mut zombies_material))) = (
+            humans_tfs_q.get_mut(humans_e),
+            zombies_tfs_q.get_mut(zombies_e),
+        ) else {
+            continue;
+        };
+
+        let population_scale =
+            (state.population as f32 / CELL_MAX_POPULATION as f32).min(1.0) * CELL_HALF_SIZE / 2.0;
+
+        let scale = Vec3::new(population_scale, population_scale, 1.0);
+
+        match state.status {
+            Status::Empty => {
+                humans_tf.scale = Vec3::ZERO;
+                zombies_tf.scale = Vec3::ZERO;
+            }
+            Status::Zombie => {
+                humans_tf.scale = Vec3::ZERO;
+                zombies_tf.scale = (scale * Vec3::new(25.0, 25.0, 1.0)).min(Vec3::new(
+                    CELL_HALF_SIZE,
+                    CELL_HALF_SIZE,
+                    1.0,
+                ));
+
+                // Tinted by the variant most of them are
+                let [runners, tanks, screamers] = state.variants;
+                let walkers = 100 - runners - tanks - screamers;
+                let dominant = [walkers, runners, tanks, screamers]
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|&(i, share)| (*share, std::cmp::Reverse(i)))
+                    .map_or(0, |(i, _)| i);
+                let material = &variant_materials.0[dominant];
+                if zombies_material.0 != *material {
+                    zombies_material.0 = material.clone();
+                }
+            }
+            Status::Human | Status::Military => {
+                humans_tf.scale = scale;
+                zombies_tf.scale = Vec3::ZERO;
+
+                // Army bases and refugee camps are tinted
+                let material = if state.status.is_military() {
+                    &military_material.0
+                } else if state.camp {
+                    &camp_material.0
+                } else {
+                    &human_material.0
+                };
+                if humans_material.0 != *material {
+                    humans_material.0 = material.clone();
+                }
+            }
+        }
+    }
+}
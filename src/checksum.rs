@@ -0,0 +1,178 @@
+use crate::config::MapConfig;
+use crate::tick::Bookkeeping;
+use crate::zombie_state::ZombieState;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+/// Rolling checksum of the grid, updated after every tick and shown in the window title,
+/// so two runs of the same session can tell the moment they diverge.
+///
+/// With `export` every tick's checksum is written to a file along with per-row hashes
+/// (`tick,checksum,row hashes`). With `verify` they're compared against such a file from
+/// another machine, and the first mismatch is written to a `divergence-<tick>.txt` report
+/// listing the differing rows and this run's cells in them.
+pub struct ChecksumPlugin {
+    pub export: Option<PathBuf>,
+    pub verify: Option<PathBuf>,
+}
+
+impl Plugin for ChecksumPlugin {
+    fn build(&self, app: &mut App) {
+        let export = self
+            .export
+            .as_ref()
+            .and_then(|path| match File::create(path) {
+                Ok(file) => Some(BufWriter::new(file)),
+                Err(err) => {
+                    error!("Checksum export disabled, can't create {path:?}: {err}");
+                    None
+                }
+            });
+        let reference = self
+            .verify
+            .as_ref()
+            .and_then(|path| match std::fs::read_to_string(path) {
+                Ok(text) => Some(text.lines().filter_map(parse_line).collect()),
+                Err(err) => {
+                    error!("Checksum verification disabled, can't read {path:?}: {err}");
+                    None
+                }
+            });
+
+        app.insert_resource(Checksum {
+            tick: 0,
+            rolling: FNV_OFFSET,
+            export,
+            reference,
+            diverged: false,
+        })
+        .add_systems(Update, update_checksum.in_set(Bookkeeping))
+        .add_systems(Update, show_checksum);
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a, unlike the std hasher it's stable across Rust versions and machines.
+struct Fnv(u64);
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Checksum {
+    tick: u64,
+    rolling: u64,
+    export: Option<BufWriter<File>>,
+    reference: Option<HashMap<u64, (u64, Vec<u64>)>>, // checksum and row hashes by tick
+    diverged: bool,
+}
+
+fn update_checksum(
+    map: Res<MapConfig>,
+    cells_q: Query<&ZombieState>,
+    mut checksum: ResMut<Checksum>,
+) {
+    let width = map.width;
+    let mut grid = vec![None; width * map.height];
+    for state in cells_q.iter() {
+        grid[state.xy.y as usize * width + state.xy.x as usize] = Some(state);
+    }
+    let rows: Vec<u64> = grid
+        .chunks(width)
+        .map(|row| {
+            let mut hasher = Fnv(FNV_OFFSET);
+            row.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect();
+
+    let mut hasher = Fnv(checksum.rolling);
+    rows.hash(&mut hasher);
+    checksum.rolling = hasher.finish();
+    checksum.tick += 1;
+    let (tick, rolling) = (checksum.tick, checksum.rolling);
+
+    if let Some(writer) = &mut checksum.export {
+        let rows_hex: Vec<String> = rows.iter().map(|row| format!("{row:016x}")).collect();
+        if let Err(err) = writeln!(writer, "{tick},{rolling:016x},{}", rows_hex.join(";")) {
+            warn!("Failed to export checksum: {err}");
+        }
+    }
+
+    if checksum.diverged {
+        return;
+    }
+    let Some(expected) = checksum
+        .reference
+        .as_ref()
+        .and_then(|reference| reference.get(&tick))
+    else {
+        return;
+    };
+    if expected.0 == rolling {
+        return;
+    }
+
+    let differing: Vec<usize> = (0..map.height)
+        .filter(|&y| expected.1.get(y) != Some(&rows[y]))
+        .collect();
+    let mut report = format!(
+        "Divergence at tick {tick}\nexpected checksum {:016x}, got {rolling:016x}\ndiffering rows: {differing:?}\n",
+        expected.0
+    );
+    // Only the first row, later ones usually differ as a consequence
+    if let Some(&y) = differing.first() {
+        report += &format!("\ncells of row {y} in this run:\n");
+        for state in grid[y * width..(y + 1) * width].iter().flatten() {
+            report += &format!("{state:?}\n");
+        }
+    }
+    let path = format!("divergence-{tick}.txt");
+    error!("Simulation diverged from the reference at tick {tick}, see {path}");
+    if let Err(err) = std::fs::write(&path, report) {
+        error!("Failed to write divergence report {path:?}: {err}");
+    }
+    checksum.diverged = true;
+}
+
+fn parse_line(line: &str) -> Option<(u64, (u64, Vec<u64>))> {
+    let mut fields = line.split(',');
+    let tick = fields.next()?.parse().ok()?;
+    let checksum = u64::from_str_radix(fields.next()?, 16).ok()?;
+    let rows = fields
+        .next()?
+        .split(';')
+        .map(|row| u64::from_str_radix(row, 16).ok())
+        .collect::<Option<_>>()?;
+    Some((tick, (checksum, rows)))
+}
+
+fn show_checksum(checksum: Res<Checksum>, mut window_q: Query<&mut Window, With<PrimaryWindow>>) {
+    if !checksum.is_changed() {
+        return;
+    }
+    for mut window in window_q.iter_mut() {
+        window.title = format!(
+            "Zombie Test - tick {}, checksum {:016x}{}",
+            checksum.tick,
+            checksum.rolling,
+            if checksum.diverged { " (diverged)" } else { "" }
+        );
+    }
+}
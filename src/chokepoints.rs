@@ -0,0 +1,52 @@
+use crate::CELL_SIZE;
+use crate::config::MapConfig;
+use crate::overlays::{Overlays, cell_to_world};
+use crate::zombie_state::ZombieState;
+use bevy::color::palettes::css::GOLD;
+use bevy::prelude::*;
+
+/// Overlay (K) marking chokepoints, the cells the garrisoning humans hold.
+pub struct ChokepointsPlugin;
+
+impl Plugin for ChokepointsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Chokepoints>().add_systems(
+            Update,
+            (
+                rebuild_chokepoints,
+                draw_chokepoints.run_if(|overlays: Res<Overlays>| overlays.chokepoints),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+struct Chokepoints(Vec<Vec2>); // world space cell centers
+
+/// The cells know whether they are chokepoints from when they were spawned.
+fn rebuild_chokepoints(
+    map: Res<MapConfig>,
+    spawned_q: Query<&ZombieState, Added<ZombieState>>,
+    mut chokepoints: ResMut<Chokepoints>,
+) {
+    if spawned_q.is_empty() {
+        return;
+    }
+
+    chokepoints.0 = spawned_q
+        .iter()
+        .filter(|state| state.chokepoint)
+        .map(|state| cell_to_world(&map, state.xy.as_vec2()))
+        .collect();
+}
+
+fn draw_chokepoints(chokepoints: Res<Chokepoints>, mut gizmos: Gizmos) {
+    for center in &chokepoints.0 {
+        gizmos.rect_2d(
+            Isometry2d::from_translation(*center),
+            Vec2::splat(CELL_SIZE * 0.8),
+            GOLD,
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::stats::{WorldStats, sample_world_stats};
+use crate::supply::Surrender;
+use crate::tick::Bookkeeping;
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Turns what happens during the run into readable lines ("Tick 340: settlement 812 is no more,
+/// its last 35 people fell or joined others"), logged as they happen and written to `path` on exit.
+pub struct ChroniclePlugin {
+    pub path: Option<PathBuf>,
+}
+
+impl Plugin for ChroniclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Headline>()
+            .insert_resource(Chronicle {
+                path: self.path.clone(),
+                tick: 0,
+                lines: Vec::new(),
+                settlements: HashMap::new(),
+            })
+            .add_systems(
+                Update,
+                (chronicle_settlements, chronicle_headlines)
+                    .chain()
+                    .in_set(ChronicleSet)
+                    .in_set(Bookkeeping)
+                    .after(sample_world_stats),
+            )
+            .add_systems(Last, write_chronicle_on_exit);
+    }
+}
+
+/// The chronicle recording a tick, headlines sent before it make it into that tick's lines.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChronicleSet;
+
+/// Something worth a line in the chronicle, sent by whoever made it happen.
+#[derive(Event, Debug, Clone)]
+pub struct Headline(pub String);
+
+#[derive(Resource)]
+struct Chronicle {
+    path: Option<PathBuf>,
+    tick: u64,
+    lines: Vec<String>,
+    settlements: HashMap<u32, i64>, // living people per founding settlement, last tick
+}
+
+impl Chronicle {
+    fn record(&mut self, text: &str) {
+        let line = format!("Tick {}: {text}", self.tick);
+        info!("{line}");
+        self.lines.push(line);
+    }
+}
+
+fn chronicle_settlements(stats: Res<WorldStats>, mut chronicle: ResMut<Chronicle>) {
+    chronicle.tick += 1;
+
+    let settlements: HashMap<u32, i64> = stats
+        .settlements()
+        .iter()
+        .map(|(&lineage, descendants)| (lineage, descendants.population))
+        .collect();
+
+    // Nothing to tell about the settlements the map was generated with
+    if chronicle.tick > 1 {
+        let mut fallen: Vec<_> = chronicle
+            .settlements
+            .iter()
+            .filter(|(lineage, _)| !settlements.contains_key(lineage))
+            .map(|(&lineage, &population)| (lineage, population))
+            .collect();
+        fallen.sort();
+        for (lineage, population) in fallen {
+            chronicle.record(&format!(
+                "settlement {lineage} is no more, its last {population} people fell or joined others"
+            ));
+        }
+        if settlements.is_empty() && !chronicle.settlements.is_empty() {
+            chronicle.record("the last humans fell, the world belongs to the dead");
+        }
+    }
+    chronicle.settlements = settlements;
+}
+
+fn chronicle_headlines(
+    mut headlines: EventReader<Headline>,
+    mut surrenders: EventReader<Surrender>,
+    mut chronicle: ResMut<Chronicle>,
+) {
+    for surrender in surrenders.read() {
+        chronicle.record(&format!(
+            "surrounded and cut off at {}, {} people of settlement {} laid down their arms",
+            surrender.xy, surrender.population, surrender.settlement
+        ));
+    }
+    for Headline(text) in headlines.read() {
+        chronicle.record(text);
+    }
+}
+
+fn write_chronicle_on_exit(mut exit_events: EventReader<AppExit>, chronicle: Res<Chronicle>) {
+    if exit_events.read().last().is_none() {
+        return;
+    }
+    if let Some(path) = &chronicle.path
+        && let Err(err) = std::fs::write(path, chronicle.lines.join("\n") + "\n")
+    {
+        error!("Failed to write the chronicle to {path:?}: {err}");
+    }
+}
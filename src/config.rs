@@ -0,0 +1,738 @@
+use crate::boundary::Boundary;
+use crate::error::{Error, Result};
+use crate::zombie_state::{MAX_INCUBATION_TICKS, ZombieState};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::Arc;
+
+pub const DEFAULT_PATH: &str = "assets/config.ron";
+
+/// Contents of the config file, anything left out keeps its default.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+struct ConfigFile {
+    map: MapConfig,
+    rules: SimulationConfig,
+}
+
+/// Reads the config file at `path`, or `assets/config.ron` if there is one.
+pub fn load(path: Option<&str>) -> Result<(MapConfig, SimulationConfig)> {
+    let path = path.unwrap_or(DEFAULT_PATH);
+    if path == DEFAULT_PATH && !Path::new(path).exists() {
+        return Ok(Default::default());
+    }
+    let text = std::fs::read_to_string(path)?;
+    let file: ConfigFile = ron::from_str(&text)?;
+    // All the problems at once, pointing at where they are set
+    let problems: Vec<String> = file
+        .problems()
+        .into_iter()
+        .map(|(field, expected)| match line_of(&text, field) {
+            Some(line) => format!("{path}:{line}: {field} should be {expected}"),
+            None => format!("{path}: {field} should be {expected}"),
+        })
+        .collect();
+    if !problems.is_empty() {
+        return Err(Error::Invalid(format!(
+            "{} problems\n{}",
+            problems.len(),
+            problems.join("\n")
+        )));
+    }
+    // Runs before the app and its logging are set up
+    println!("Config loaded from {path}");
+    Ok((file.map, file.rules))
+}
+
+impl ConfigFile {
+    /// Values the simulation can't run with, as the field and what's expected of it.
+    fn problems(&self) -> Vec<(&'static str, &'static str)> {
+        let (map, rules) = (&self.map, &self.rules);
+        let mut problems = Vec::new();
+        let mut check = |field: &'static str, ok: bool, expected: &'static str| {
+            if !ok {
+                problems.push((field, expected));
+            }
+        };
+
+        check("width", map.width >= 1, "at least 1");
+        check("height", map.height >= 1, "at least 1");
+        check("octaves", map.octaves >= 1, "at least 1");
+        check("terrain_scale", map.terrain_scale > 0.0, "above 0");
+        check(
+            "tick_time_step",
+            map.tick_time_step.is_none_or(|step| step > 0.0),
+            "above 0",
+        );
+        check(
+            "boundary",
+            map.boundary != Boundary::Wrap || (map.width >= 3 && map.height >= 3),
+            "Wall or Open on maps narrower than 3 cells",
+        );
+        let size = IVec2::new(map.width as i32, map.height as i32);
+        check(
+            "barriers",
+            map.barriers.iter().all(|barrier| {
+                barrier.min.cmpge(IVec2::ZERO).all()
+                    && barrier.min.cmple(barrier.max).all()
+                    && barrier.max.cmplt(size).all()
+            }),
+            "on the map, each min no greater than its max",
+        );
+        check(
+            "barriers",
+            map.barriers.iter().enumerate().all(|(i, barrier)| {
+                map.barriers[i + 1..]
+                    .iter()
+                    .all(|other| !barrier.overlaps(other.min, other.max))
+            }),
+            "not overlapping each other",
+        );
+        check(
+            "water_level",
+            (-1.0..=1.0).contains(&map.water_level),
+            "between -1 and 1, the altitude's range",
+        );
+        let settlements = map.settlements;
+        check(
+            "settlements",
+            settlements.city_radius >= 0
+                && settlements.outbreak_radius >= 0
+                && settlements.army_base_radius >= 0,
+            "radii of at least 0",
+        );
+        check(
+            "settlements",
+            settlements.city_population >= 1
+                && settlements.outbreak_population >= 1
+                && settlements.army_base_population >= 1,
+            "populations of at least 1",
+        );
+        check(
+            "settlements",
+            settlements.factions >= 1,
+            "at least 1 faction",
+        );
+        check(
+            "settlements",
+            settlements
+                .outbreak_variants
+                .iter()
+                .all(|&share| share >= 0)
+                && settlements.outbreak_variants.iter().sum::<i32>() <= 100,
+            "outbreak_variants of at least 0, adding up to no more than 100",
+        );
+        check(
+            "goals",
+            map.goals.iter().all(|goal| match *goal {
+                Goal::Hold { min, max, ticks } => {
+                    min.cmpge(IVec2::ZERO).all()
+                        && min.cmple(max).all()
+                        && max.cmplt(size).all()
+                        && ticks >= 1
+                }
+                Goal::Evacuate { humans } => humans >= 1,
+                Goal::Eradicate { .. } => true,
+            }),
+            "regions on the map held for at least 1 tick, at least 1 human to evacuate",
+        );
+        check(
+            "goals",
+            map.goals.iter().all(|goal| match *goal {
+                Goal::Hold { min, max, .. } => !map
+                    .barriers
+                    .iter()
+                    .any(|barrier| barrier.contains(min) && barrier.contains(max)),
+                Goal::Evacuate { .. } | Goal::Eradicate { .. } => true,
+            }),
+            "regions to hold not walled off by a barrier, they'd be held from the start",
+        );
+
+        // Divisors and roll ranges
+        check("attack_ratio", rules.attack_ratio >= 1, "at least 1");
+        check(
+            "conversion_divisor",
+            rules.conversion_divisor >= 1,
+            "at least 1",
+        );
+        check(
+            "incubation_ticks",
+            (1..=MAX_INCUBATION_TICKS as i32).contains(&rules.incubation_ticks),
+            "between 1 and 16",
+        );
+        check(
+            "hide_detection_roll",
+            rules.hide_detection_roll >= 1,
+            "at least 1",
+        );
+        check("people_per_food", rules.people_per_food >= 1, "at least 1");
+        check("smell_max", rules.smell_max >= 1, "at least 1");
+        check(
+            "military_combat_multiplier",
+            rules.military_combat_multiplier >= 1,
+            "at least 1",
+        );
+        check("tank_weight", rules.tank_weight >= 1, "at least 1");
+        check(
+            "cure_research_per_lab",
+            rules.cure_research_per_lab >= 0,
+            "at least 0",
+        );
+        check(
+            "cure_percent",
+            (0..=12).contains(&rules.cure_percent),
+            "between 0 and 12, zombie cells can have 8 human neighbors",
+        );
+        check(
+            "screamer_smell_percent",
+            rules.screamer_smell_percent >= 0,
+            "at least 0",
+        );
+        check(
+            "military_fire_percent",
+            rules.military_fire_percent >= 0,
+            "at least 0",
+        );
+        // A bonus of -100% or less would leave attackers uphill with no strength, or less than none
+        check(
+            "high_ground_bonus_percent",
+            rules.high_ground_bonus_percent >= 0,
+            "at least 0",
+        );
+        check(
+            "high_ground_max_bonus_percent",
+            rules.high_ground_max_bonus_percent >= 0,
+            "at least 0",
+        );
+        check("day_ticks", rules.day_ticks >= 2, "at least 2");
+        check(
+            "season_temperature_swing",
+            rules.season_temperature_swing >= 0,
+            "at least 0",
+        );
+        check(
+            "wind",
+            (0..=8).contains(&rules.wind.direction),
+            "a direction of 0 to 7, or 8 for calm",
+        );
+        check(
+            "wind",
+            (0..=100).contains(&rules.wind.strength_percent),
+            "a strength_percent between 0 and 100",
+        );
+        check(
+            "fortification_max",
+            rules.fortification_max >= 0,
+            "at least 0",
+        );
+        check(
+            "move_percent",
+            (1..=100).contains(&rules.move_percent),
+            "between 1 and 100",
+        );
+        check(
+            "road_throughput_percent",
+            rules.road_throughput_percent >= 100,
+            "at least 100",
+        );
+        check(
+            "event_roll_every_ticks",
+            rules.event_roll_every_ticks >= 1,
+            "at least 1",
+        );
+        check(
+            "birth_rate",
+            (0.0..=1.0).contains(&rules.birth_rate),
+            "between 0 and 1",
+        );
+        for (field, percent) in [
+            ("smell_spread_percent", rules.smell_spread_percent),
+            ("smell_decay_percent", rules.smell_decay_percent),
+            ("dry_smell_loss_percent", rules.dry_smell_loss_percent),
+            ("noise_carry_percent", rules.noise_carry_percent),
+            ("noise_linger_percent", rules.noise_linger_percent),
+            ("camp_disease_percent", rules.camp_disease_percent),
+            ("heat_decay_percent", rules.heat_decay_percent),
+            ("corpse_rot_percent", rules.corpse_rot_percent),
+            ("cut_off_attrition_percent", rules.cut_off_attrition_percent),
+            ("famine_deaths_percent", rules.famine_deaths_percent),
+            ("surrender_turned_percent", rules.surrender_turned_percent),
+            ("intel_decay_percent", rules.intel_decay_percent),
+            ("crossing_max_loss_percent", rules.crossing_max_loss_percent),
+            ("wall_smell_percent", rules.wall_smell_percent),
+            ("bitten_percent", rules.bitten_percent),
+            ("rain_washout_percent", rules.rain_washout_percent),
+            ("fog_sight_loss_percent", rules.fog_sight_loss_percent),
+            ("snow_slow_percent", rules.snow_slow_percent),
+            ("event_chance_percent", rules.event_chance_percent),
+        ] {
+            check(field, (0..=100).contains(&percent), "between 0 and 100");
+        }
+        problems
+    }
+}
+
+/// Line (from 1) `field` is set on in the config text, if it's set at all.
+fn line_of(text: &str, field: &str) -> Option<usize> {
+    text.lines()
+        .position(|line| {
+            line.trim_start()
+                .strip_prefix(field)
+                .is_some_and(|rest| rest.trim_start().starts_with(':'))
+        })
+        .map(|index| index + 1)
+}
+
+/// World generation and pacing, fixed for the whole run.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct MapConfig {
+    pub width: usize,  // cells
+    pub height: usize, // cells
+    pub seed: u64,
+    pub octaves: i32,       // noise levels the terrain is built from
+    pub terrain_scale: f64, // size in cells of the largest terrain features
+    pub water_level: f32,   // altitude (-1..1) below which cells are water
+    pub boundary: Boundary,
+    pub barriers: Vec<Barrier>, // impassable cells placed by hand, on top of the generated terrain
+    pub tick_time_step: Option<f64>, // seconds per tick, none ticks on every frame
+    pub settlements: Settlements,
+    pub goals: Vec<Goal>, // what the humans are asked to do, none for a sandbox
+    pub stalemate_ticks: u64, // with no cell changing hands before the run ends as a stalemate, 0 never
+}
+
+/// Block of cells from `min` to `max` (inclusive) nobody can cross, e.g. a cliff or a pre-built wall.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Barrier {
+    pub min: IVec2,
+    pub max: IVec2,
+}
+
+impl Barrier {
+    pub fn contains(&self, xy: IVec2) -> bool {
+        xy.cmpge(self.min).all() && xy.cmple(self.max).all()
+    }
+
+    /// Whether any cell from `min` to `max` (inclusive) is in the barrier.
+    pub fn overlaps(&self, min: IVec2, max: IVec2) -> bool {
+        min.cmple(self.max).all() && max.cmpge(self.min).all()
+    }
+}
+
+/// Who the map starts with: a city of humans at every city site of the terrain, thinning out
+/// toward its edge, a few zombie outbreaks away from them and a few army bases anywhere.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct Settlements {
+    pub city_radius: i32,     // cells from the center to the last inhabited ring
+    pub city_population: i32, // at the center
+    pub outbreaks: u32,
+    pub outbreak_radius: i32,
+    pub outbreak_population: i32,
+    pub army_bases: u32,
+    pub army_base_radius: i32,
+    pub army_base_population: i32,
+    pub factions: u8,                // of humans, not joining each other
+    pub outbreak_variants: [i32; 3], // percent of runners, tanks and screamers
+}
+
+impl Default for MapConfig {
+    fn default() -> Self {
+        MapConfig {
+            width: 150,
+            height: 75,
+            seed: 42,
+            octaves: 5,
+            terrain_scale: 100.0,
+            water_level: -0.3,
+            boundary: Boundary::Wall,
+            barriers: Vec::new(),
+            tick_time_step: Some(0.1),
+            settlements: Settlements::default(),
+            goals: Vec::new(),
+            stalemate_ticks: 2000,
+        }
+    }
+}
+
+impl Default for Settlements {
+    fn default() -> Self {
+        Settlements {
+            city_radius: 5,
+            city_population: 150,
+            outbreaks: 4,
+            outbreak_radius: 1,
+            outbreak_population: 10,
+            army_bases: 2,
+            army_base_radius: 1,
+            army_base_population: 20,
+            factions: 1,
+            outbreak_variants: [10, 5, 5],
+        }
+    }
+}
+
+/// What the humans are asked to do, set in [`MapConfig::goals`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum Goal {
+    Hold { min: IVec2, max: IVec2, ticks: u64 }, // every passable cell in the block human for that many ticks in a row
+    Evacuate { humans: i64 },                    // off the open edges of the map
+    Eradicate { before_tick: u64 },              // not a zombie left
+}
+
+/// Tuning of the simulation rules, changing the resource applies from the next tick.
+#[derive(Resource, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct SimulationConfig {
+    // Combat and infection
+    pub holder_advantage: i32, // zombies a human holding its cell takes out
+    pub attack_ratio: i32,     // humans attack zombies they outnumber this many to one
+    pub conversion_divisor: i32, // one in this many humans killed rises as a zombie
+    pub bitten_percent: i32,   // of the humans killed, as many survivors are infected
+    pub incubation_ticks: i32, // the infected turn this many ticks after they were bitten
+    pub fortify_per_tick: i32, // fortification humans holding still add every tick
+    pub fortification_max: i32, // percent added to the holder advantage at most
+
+    // Growth
+    pub birth_rate: f32,      // per tick, in human cells that are calm and fed
+    pub birth_calm_ticks: u8, // ticks without fighting or anyone coming or going before humans grow
+    pub birth_min_food: i32,  // food stored before humans grow
+
+    // Movement: only part of a group sets off, the rest stays behind and keeps the cell
+    pub move_percent: i32,
+
+    // Smell: spread between neighbors and fading every tick, plus what the cell's own population gives off
+    pub smell_spread_percent: i32, // share of a cell's smell swapped for its neighbors' average
+    pub smell_decay_percent: i32,  // share lost every tick
+    pub smell_per_population: i32,
+    pub smell_max: i32, // per cell, in units of one population's smell
+    pub dry_smell_loss_percent: i32, // lost on top of the decay, on the driest ground
+
+    // Combat noise: spikes where a battle is fought, hops one cell per tick and fades fast
+    pub noise_per_casualty: i32,
+    pub noise_carry_percent: i32, // share of a neighbor's noise that reaches us over flat ground
+    pub noise_climb_penalty_percent: i32, // carry lost per altitude unit the sound has to climb
+    pub noise_linger_percent: i32, // share of our own noise that is still around next tick
+    pub noise_heard_min: i32, // quieter noise doesn't lure zombies, louder is followed before any smell
+    pub zombie_idle: ZombieIdle, // what zombies do with nothing loud enough to hear nor any smell
+
+    // Panic: a fallen human cell sends nearby humans fleeing away from it
+    pub panic_strength: i32,
+    pub panic_falloff: i32, // lost per cell it spreads
+    pub panic_decay: i32,   // lost per tick it lingers in a cell
+
+    // Refugee camps: overcrowded cells get sick and fight worse until people disperse
+    pub cell_capacity: i32,
+    pub camp_min_inflow: i32, // refugees arriving in one tick that can start a camp
+    pub camp_disease_percent: i32, // population lost to disease per tick
+    pub camp_holder_advantage: i32, // instead of holder_advantage
+
+    // Temperature: the cold slows zombies down, the heat rots them
+    pub freezing_temperature: i32, // below it zombies only act every frozen_move_every ticks
+    pub frozen_move_every: u8,
+    pub heat_temperature: i32,         // above it zombies decay
+    pub heat_decay_percent: i32,       // population lost per tick, at least one
+    pub season_ticks: u32,             // each of the four seasons, 0 for none
+    pub season_temperature_swing: i32, // added in summer, taken away in winter
+
+    // Corpses: left by every death, rotting away over time
+    pub corpse_rot_percent: i32, // share rotting away per tick, at least one
+    pub corpse_sustain_percent: i32, // heat decay slowed by this much per corpse in the cell
+    pub corpse_smell_percent: i32, // human smell given off per corpse, the hordes come to feed
+
+    // High ground: attackers climbing into a held cell count for less
+    pub high_ground_bonus_percent: i32, // defender bonus per altitude unit the attacker climbs
+    pub high_ground_max_bonus_percent: i32,
+
+    // Steep ground: groups moving across it lose some of their members on the way
+    pub crossing_free_step: i32, // altitude units a group crosses without losses
+    pub crossing_loss_percent: i32, // lost per altitude unit above the free step, up or down
+    pub zombie_climb_loss_percent: i32, // lost by zombies per altitude unit climbed, on top
+    pub crossing_max_loss_percent: i32,
+    pub wall_smell_percent: i32, // share of the smell a wall lets through
+    pub road_throughput_percent: i32, // along roads, stragglers are divided and frozen zombies wait divided by it
+
+    // Food: grown by the land, more in warm weather, eaten by the humans living on it
+    pub food_per_tick: i32, // grown on plains at temperature 0, twice that at the hottest
+    pub food_capacity: i32, // most a cell stores, every cell starts full
+    pub people_per_food: i32, // fed by one unit of food for a tick
+    pub famine_deaths_percent: i32, // of the unfed, lost per tick, at least one
+
+    // Supply lines: frontline humans cut off from their settlements starve and lose heart
+    pub cut_off_attrition_percent: i32, // population lost per tick, at least one
+    pub cut_off_holder_advantage: i32,  // instead of holder_advantage
+    pub surrender_after_ticks: u8,      // surrounded by zombies and cut off for this long
+    pub surrender_turned_percent: i32, // share of the fallen that turn, instead of one in conversion_divisor
+
+    // Hiding: small groups of survivors lie low while zombies are around
+    pub hide_max_population: i32,
+    pub hide_detection_roll: i32, // a horde spots hidden survivors if it's larger than a roll of 0..this
+
+    // Intel: humans act on the zombie smell they've seen, which fades from memory when nobody looks
+    pub sight_max_climb: i32, // a cell this much higher than the human one blocks the view into it
+    pub intel_decay_percent: i32, // share of the remembered smell forgotten per tick
+
+    // Secured ground: held with no zombie in sight or smell, humans grow faster there
+    pub secure_after_ticks: u8, // also how long it stays secured once the humans leave
+    pub secured_birth_bonus_percent: i32,
+
+    // Wind: the smell drifts downwind
+    pub wind: Wind,
+
+    // Day and night: zombies find and press harder in the dark, humans build and shoot better by day
+    pub day_ticks: u32, // a whole day, the second half of it is night
+    pub night_detection_bonus_percent: i32, // added to a horde's size when rolling to spot the hiding
+    pub night_attack_bonus_percent: i32,
+    pub day_holder_bonus_percent: i32, // added to the holder advantage, like fortification
+    pub day_fortify_bonus_percent: i32,
+    pub day_fire_bonus_percent: i32, // added to the soldiers' fire, see military_fire_percent
+
+    // Weather: fronts of rain, fog and snow crossing the map
+    pub rain_washout_percent: i32, // smell washed away per tick, on top of the decay
+    pub fog_sight_loss_percent: i32, // of the zombie smell the humans make out
+    pub snow_slow_percent: i32,    // taken off the share of a group that moves
+
+    // Military: army bases hold their ground and fire on the zombies next to them
+    pub military_combat_multiplier: i32, // of the holder advantage
+    pub military_fire_percent: i32,      // of the soldiers, zombies shot in every neighbor per tick
+
+    // Zombie variants: runners never hang back, tanks take more to bring down, screamers reek
+    pub tank_weight: i32,            // in combat, 1 for a walker
+    pub screamer_smell_percent: i32, // of a walker's smell
+
+    // Cure: researched in labs, once ready the humans stop turning and bring the zombies back
+    pub lab_min_population: i32, // of a secured human cell to run a lab
+    pub cure_research_per_lab: i64, // every tick
+    pub cure_research_needed: i64,
+    pub cure_percent: i32, // of a zombie cell brought back every tick by each human cell next to it
+
+    // Random events: now and then one is picked from a weighted table and hits the map
+    pub event_roll_every_ticks: u32,
+    pub event_chance_percent: i32, // of an event on each roll
+    pub event_weights: EventWeights,
+}
+
+/// What zombies do when no neighbor has anything to follow.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ZombieIdle {
+    #[default]
+    Drift, // toward the coldest, lowest neighbor
+    Wander,      // a random walk, rolled from the cell's state
+    Stay,        // wait where they are
+    TowardNoise, // follow even noise too faint to make out, otherwise wait
+}
+
+/// Wind over the whole map, the smell comes more from upwind neighbors and less from downwind ones.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct Wind {
+    pub direction: i8, // blowing toward, 0-7 clockwise from the north, 8 when calm
+    pub strength_percent: i32, // extra weight of the smell from straight upwind, taken off straight downwind
+}
+
+impl Wind {
+    pub const CALM: Wind = Wind {
+        direction: 8,
+        strength_percent: 0,
+    };
+
+    /// Weight of the smell from the neighbor in `direction` in its cell's average, 100 across the wind.
+    pub fn weight_percent(&self, direction: i8) -> i32 {
+        if self.direction == 8 {
+            return 100;
+        }
+        let upwind = (self.direction + 4) % 8;
+        let steps = (direction - upwind)
+            .rem_euclid(8)
+            .min((upwind - direction).rem_euclid(8));
+        100 + self.strength_percent * (2 - steps as i32) / 2
+    }
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Wind::CALM
+    }
+}
+
+/// How likely each random event is to be the one picked, 0 never picks it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct EventWeights {
+    pub fuel_depot_explosion: u32,
+    pub survivor_broadcast: u32,
+    pub cold_snap: u32,
+}
+
+impl EventWeights {
+    pub const DEFAULT: EventWeights = EventWeights {
+        fuel_depot_explosion: 3,
+        survivor_broadcast: 2,
+        cold_snap: 1,
+    };
+}
+
+impl Default for EventWeights {
+    fn default() -> Self {
+        EventWeights::DEFAULT
+    }
+}
+
+impl SimulationConfig {
+    pub const DEFAULT: SimulationConfig = SimulationConfig {
+        holder_advantage: 3,
+        attack_ratio: 3,
+        conversion_divisor: 3,
+        bitten_percent: 30,
+        incubation_ticks: 10,
+        fortify_per_tick: 2,
+        fortification_max: 100,
+        birth_rate: 0.01,
+        birth_calm_ticks: 10,
+        birth_min_food: 100,
+        move_percent: 50,
+        smell_spread_percent: 50,
+        smell_decay_percent: 5,
+        smell_per_population: 1,
+        smell_max: 10_000,
+        dry_smell_loss_percent: 10,
+        noise_per_casualty: 1,
+        noise_carry_percent: 70,
+        noise_climb_penalty_percent: 2,
+        noise_linger_percent: 30,
+        noise_heard_min: 5,
+        zombie_idle: ZombieIdle::Drift,
+        panic_strength: 6,
+        panic_falloff: 1,
+        panic_decay: 2,
+        cell_capacity: 1000,
+        camp_min_inflow: 200,
+        camp_disease_percent: 3,
+        camp_holder_advantage: 2,
+        freezing_temperature: -30,
+        frozen_move_every: 3,
+        heat_temperature: 30,
+        heat_decay_percent: 2,
+        season_ticks: 500,
+        season_temperature_swing: 30,
+        corpse_rot_percent: 5,
+        corpse_sustain_percent: 2,
+        corpse_smell_percent: 50,
+        high_ground_bonus_percent: 10,
+        high_ground_max_bonus_percent: 200,
+        crossing_free_step: 2,
+        crossing_loss_percent: 5,
+        zombie_climb_loss_percent: 3,
+        crossing_max_loss_percent: 60,
+        wall_smell_percent: 20,
+        road_throughput_percent: 200,
+        food_per_tick: 20,
+        food_capacity: 600,
+        people_per_food: 10,
+        famine_deaths_percent: 10,
+        cut_off_attrition_percent: 2,
+        cut_off_holder_advantage: 2,
+        surrender_after_ticks: 20,
+        surrender_turned_percent: 60,
+        hide_max_population: 20,
+        hide_detection_roll: 50,
+        sight_max_climb: 5,
+        intel_decay_percent: 10,
+        secure_after_ticks: 30,
+        secured_birth_bonus_percent: 50,
+        wind: Wind::CALM,
+        day_ticks: 240,
+        night_detection_bonus_percent: 50,
+        night_attack_bonus_percent: 20,
+        day_holder_bonus_percent: 30,
+        day_fortify_bonus_percent: 50,
+        day_fire_bonus_percent: 50,
+        rain_washout_percent: 20,
+        fog_sight_loss_percent: 50,
+        snow_slow_percent: 50,
+        military_combat_multiplier: 3,
+        military_fire_percent: 25,
+        tank_weight: 3,
+        screamer_smell_percent: 300,
+        lab_min_population: 300,
+        cure_research_per_lab: 1,
+        cure_research_needed: 20_000,
+        cure_percent: 5,
+        event_roll_every_ticks: 50,
+        event_chance_percent: 20,
+        event_weights: EventWeights::DEFAULT,
+    };
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig::DEFAULT
+    }
+}
+
+/// The [`SimulationConfig`] a cell is updated with. The cell update has no access to resources,
+/// so every cell carries one along, all of them sharing the same config.
+///
+/// Left out of the cell's hash and its save, the whole map has the same rules.
+#[derive(Clone, Default)]
+pub struct Rules(Arc<SimulationConfig>);
+
+impl Rules {
+    pub fn new(config: SimulationConfig) -> Self {
+        Rules(Arc::new(config))
+    }
+}
+
+impl Deref for Rules {
+    type Target = SimulationConfig;
+
+    fn deref(&self) -> &SimulationConfig {
+        &self.0
+    }
+}
+
+impl PartialEq for Rules {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.0 == other.0
+    }
+}
+
+// A loaded config has no NaN in it, see the birth_rate check
+impl Eq for Rules {}
+
+impl Hash for Rules {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+impl fmt::Debug for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Rules { .. }")
+    }
+}
+
+/// Inserts the default [`SimulationConfig`] unless one was inserted before,
+/// and hands it on to the cells whenever it changes.
+pub struct SimulationConfigPlugin;
+
+impl Plugin for SimulationConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationConfig>().add_systems(
+            First,
+            sync_rules.run_if(resource_changed::<SimulationConfig>),
+        );
+    }
+}
+
+fn sync_rules(config: Res<SimulationConfig>, mut cells_q: Query<&mut ZombieState>) {
+    let rules = Rules::new(*config);
+    for mut state in cells_q.iter_mut() {
+        // Only the cells with other rules count as changed
+        if state.rules != rules {
+            state.rules = rules.clone();
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::Terrain;
+use crate::config::MapConfig;
+use crate::overlays::{Overlays, cell_to_world};
+use bevy::prelude::*;
+
+/// Altitude contour lines, built by marching squares over the cell centers.
+pub struct ContoursPlugin;
+
+impl Plugin for ContoursPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ContourSettings>()
+            .init_resource::<Contours>()
+            .add_systems(
+                Update,
+                (
+                    rebuild_contours,
+                    draw_contours.run_if(|overlays: Res<Overlays>| overlays.contours),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct ContourSettings {
+    pub interval: f32,    // altitude units between lines, altitude spans -1..1
+    pub major_every: i32, // every n-th line is drawn brighter
+}
+
+impl Default for ContourSettings {
+    fn default() -> Self {
+        ContourSettings {
+            interval: 0.05,
+            major_every: 5,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct Contours {
+    segments: Vec<(Vec2, Vec2, bool)>, // world space start, end, is major
+}
+
+fn rebuild_contours(
+    map: Res<MapConfig>,
+    terrain: Option<Res<Terrain>>,
+    settings: Res<ContourSettings>,
+    mut contours: ResMut<Contours>,
+) {
+    let Some(terrain) = terrain else {
+        return;
+    };
+    if !terrain.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    let altitude = |x: usize, y: usize| terrain.0[y][x][0];
+    let (width, height) = (terrain.0[0].len(), terrain.0.len());
+    let (min, max) = terrain
+        .0
+        .iter()
+        .flatten()
+        .map(|cell| cell[0])
+        .fold((f32::MAX, f32::MIN), |(min, max), a| {
+            (min.min(a), max.max(a))
+        });
+
+    contours.segments.clear();
+    let first = (min / settings.interval).ceil() as i32;
+    let last = (max / settings.interval).floor() as i32;
+    for index in first..=last {
+        let level = index as f32 * settings.interval;
+        let major = index % settings.major_every == 0;
+        for y in 0..height - 1 {
+            for x in 0..width - 1 {
+                // Corners counter-clockwise from the bottom left
+                let corners = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)]
+                    .map(|(cx, cy)| (Vec2::new(cx as f32, cy as f32), altitude(cx, cy)));
+                for (start, end) in square_segments(corners, level) {
+                    contours.segments.push((
+                        cell_to_world(&map, start),
+                        cell_to_world(&map, end),
+                        major,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Contour segments crossing one square, in cell coordinates.
+fn square_segments(corners: [(Vec2, f32); 4], level: f32) -> Vec<(Vec2, Vec2)> {
+    // Edge i runs from corner i to corner i + 1, so corner i touches edges i - 1 and i
+    let crossings: [Option<Vec2>; 4] = std::array::from_fn(|i| {
+        let ((p0, a0), (p1, a1)) = (corners[i], corners[(i + 1) % 4]);
+        if (a0 >= level) == (a1 >= level) {
+            return None;
+        }
+        Some(p0.lerp(p1, (level - a0) / (a1 - a0)))
+    });
+
+    let found: Vec<Vec2> = crossings.iter().flatten().copied().collect();
+    match found[..] {
+        [start, end] => vec![(start, end)],
+        [_, _, _, _] => {
+            // Saddle, cut off the corners that disagree with the square's center
+            let center_above = corners.iter().map(|(_, a)| a).sum::<f32>() / 4.0 >= level;
+            (0..4)
+                .filter(|&i| (corners[i].1 >= level) != center_above)
+                .map(|i| (crossings[(i + 3) % 4].unwrap(), crossings[i].unwrap()))
+                .collect()
+        }
+        _ => vec![],
+    }
+}
+
+fn draw_contours(contours: Res<Contours>, mut gizmos: Gizmos) {
+    for &(start, end, major) in &contours.segments {
+        let color = if major {
+            Color::srgba(0.25, 0.15, 0.05, 0.9)
+        } else {
+            Color::srgba(0.25, 0.15, 0.05, 0.45)
+        };
+        gizmos.line_2d(start, end, color);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::config::MapConfig;
+use crate::loading::LoadingState;
+use crate::overlays::{Overlays, map_image, map_sprite};
+use crate::zombie_state::ZombieState;
+use bevy::prelude::*;
+
+const STAIN_FULL_AT: f32 = 200.0; // corpses that make the darkest stain
+
+/// Overlay (X) staining the ground where the fallen still lie.
+pub struct CorpsesPlugin;
+
+impl Plugin for CorpsesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(LoadingState::BuildingViews), setup_corpses_view)
+            .add_systems(
+                Update,
+                update_corpses_view.run_if(in_state(LoadingState::Ready)),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct CorpsesImage(Handle<Image>);
+
+#[derive(Component)]
+struct CorpsesView;
+
+fn setup_corpses_view(
+    map: Res<MapConfig>,
+    mut images: ResMut<Assets<Image>>,
+    mut commands: Commands,
+) {
+    let image = images.add(map_image(map.width, map.height, |_, _| [0, 0, 0, 0]));
+    commands.spawn((
+        map_sprite(&map, image.clone(), 1.5),
+        Visibility::Hidden,
+        CorpsesView,
+    ));
+    commands.insert_resource(CorpsesImage(image));
+}
+
+fn update_corpses_view(
+    map: Res<MapConfig>,
+    overlays: Res<Overlays>,
+    cells_q: Query<&ZombieState>,
+    corpses_image: Res<CorpsesImage>,
+    mut images: ResMut<Assets<Image>>,
+    mut view_q: Query<&mut Visibility, With<CorpsesView>>,
+) {
+    for mut visibility in view_q.iter_mut() {
+        *visibility = if overlays.corpses {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+    if !overlays.corpses {
+        return;
+    }
+
+    let mut corpses = vec![0; map.width * map.height];
+    for state in cells_q.iter() {
+        corpses[state.xy.y as usize * map.width + state.xy.x as usize] = state.corpses;
+    }
+    if let Some(image) = images.get_mut(&corpses_image.0) {
+        // Dark red-brown, more opaque the more bodies
+        *image = map_image(map.width, map.height, |x, y| {
+            let stain = (corpses[y * map.width + x] as f32 / STAIN_FULL_AT).min(1.0);
+            [60, 15, 10, (stain * 200.0) as u8]
+        });
+    }
+}
@@ -0,0 +1,66 @@
+use crate::chronicle::Headline;
+use crate::config::SimulationConfig;
+use crate::tick::Bookkeeping;
+use crate::zombie_state::ZombieState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Research toward a cure, done in the labs of big human cells held secure. Once it's ready
+/// the cells are told: the humans stop turning and bring the zombies next to them back.
+pub struct CurePlugin;
+
+impl Plugin for CurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CureProgress>()
+            .add_systems(Update, research_cure.in_set(Bookkeeping));
+    }
+}
+
+/// Research done so far, see SimulationConfig::cure_research_needed.
+#[derive(Resource, Default, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct CureProgress {
+    pub research: i64,
+    pub labs: usize, // working on it on the last tick
+}
+
+impl CureProgress {
+    #[cfg(feature = "gui")]
+    pub fn percent(&self, rules: &SimulationConfig) -> f64 {
+        (self.research as f64 * 100.0 / rules.cure_research_needed.max(1) as f64).min(100.0)
+    }
+
+    pub fn is_ready(&self, rules: &SimulationConfig) -> bool {
+        self.research >= rules.cure_research_needed
+    }
+}
+
+fn research_cure(
+    rules: Res<SimulationConfig>,
+    mut progress: ResMut<CureProgress>,
+    mut cells_q: Query<&mut ZombieState>,
+    mut headlines: EventWriter<Headline>,
+) {
+    let was_ready = progress.is_ready(&rules);
+    progress.labs = cells_q
+        .iter()
+        .filter(|state| {
+            state.status.is_human() && state.secured && state.population >= rules.lab_min_population
+        })
+        .count();
+    if !was_ready {
+        progress.research += progress.labs as i64 * rules.cure_research_per_lab;
+    }
+    let ready = progress.is_ready(&rules);
+    if ready && !was_ready {
+        headlines.send(Headline(format!(
+            "the cure is ready, researched in {} labs",
+            progress.labs
+        )));
+    }
+    for mut state in cells_q.iter_mut() {
+        // Only touched once, so the other cells aren't marked changed
+        if state.cured != ready {
+            state.cured = ready;
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::config::SimulationConfig;
+use crate::tick::Bookkeeping;
+use crate::zombie_state::ZombieState;
+#[cfg(feature = "gui")]
+use crate::{
+    config::MapConfig,
+    loading::LoadingState,
+    overlays::{map_image, map_sprite},
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "gui")]
+const NIGHT_TINT: [u8; 3] = [10, 15, 60];
+#[cfg(feature = "gui")]
+const NIGHT_TINT_MAX_ALPHA: f32 = 0.45; // at midnight
+
+/// Day/night cycle advanced every tick, the cells are told when night falls and when it's over.
+pub struct DayNightPlugin;
+
+impl Plugin for DayNightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeOfDay>()
+            .add_systems(Update, advance_time_of_day.in_set(Bookkeeping));
+    }
+}
+
+/// Darkens the terrain with the time of day.
+#[cfg(feature = "gui")]
+pub struct NightTintPlugin;
+
+#[cfg(feature = "gui")]
+impl Plugin for NightTintPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(LoadingState::BuildingViews), setup_night_tint)
+            .add_systems(
+                Update,
+                update_night_tint.run_if(in_state(LoadingState::Ready)),
+            );
+    }
+}
+
+/// Ticks since the first dawn.
+#[derive(Resource, Default, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct TimeOfDay(pub u64);
+
+impl TimeOfDay {
+    /// Share of the day gone, 0 at dawn, 0.5 at dusk.
+    pub fn phase(&self, day_ticks: u32) -> f32 {
+        (self.0 % day_ticks as u64) as f32 / day_ticks as f32
+    }
+
+    pub fn is_night(&self, day_ticks: u32) -> bool {
+        self.phase(day_ticks) >= 0.5
+    }
+
+    /// 0 at noon, 1 at midnight.
+    #[cfg(feature = "gui")]
+    pub fn darkness(&self, day_ticks: u32) -> f32 {
+        (1.0 - (self.phase(day_ticks) * std::f32::consts::TAU - std::f32::consts::FRAC_PI_2).cos())
+            / 2.0
+    }
+}
+
+fn advance_time_of_day(
+    rules: Res<SimulationConfig>,
+    mut time: ResMut<TimeOfDay>,
+    mut cells_q: Query<&mut ZombieState>,
+) {
+    time.0 += 1;
+    let night = time.is_night(rules.day_ticks);
+    for mut state in cells_q.iter_mut() {
+        // Only touched at dusk and dawn, so the other cells aren't marked changed
+        if state.night != night {
+            state.night = night;
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+#[derive(Component)]
+struct NightTint;
+
+#[cfg(feature = "gui")]
+fn setup_night_tint(
+    map: Res<MapConfig>,
+    mut images: ResMut<Assets<Image>>,
+    mut commands: Commands,
+) {
+    let [r, g, b] = NIGHT_TINT;
+    let image = images.add(map_image(1, 1, |_, _| [r, g, b, 255]));
+    // Over the terrain, under the populations
+    let (mut sprite, transform) = map_sprite(&map, image, 1.1);
+    sprite.color = Color::NONE;
+    commands.spawn((sprite, transform, NightTint));
+}
+
+#[cfg(feature = "gui")]
+fn update_night_tint(
+    rules: Res<SimulationConfig>,
+    time: Res<TimeOfDay>,
+    mut tint_q: Query<&mut Sprite, With<NightTint>>,
+) {
+    if !time.is_changed() {
+        return;
+    }
+    let alpha = time.darkness(rules.day_ticks) * NIGHT_TINT_MAX_ALPHA;
+    for mut sprite in tint_q.iter_mut() {
+        sprite.color = Color::srgba(1.0, 1.0, 1.0, alpha);
+    }
+}
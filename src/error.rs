@@ -0,0 +1,43 @@
+use std::fmt;
+
+/// Recoverable errors, reported to the user rather than taking the app down.
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError), // with the line and column it happened at
+    Serialize(ron::Error),
+    Invalid(String), // well-formed but unusable data
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{err}"),
+            Error::Parse(err) => write!(f, "{err}"),
+            Error::Serialize(err) => write!(f, "{err}"),
+            Error::Invalid(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for Error {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<ron::Error> for Error {
+    fn from(err: ron::Error) -> Self {
+        Error::Serialize(err)
+    }
+}
@@ -0,0 +1,115 @@
+use crate::tick::Bookkeeping;
+use crate::zombie_state::{Status, ZombieState, direction_to_delta};
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+/// Exports every population movement as a CSV edge list (`Source,Target,Weight,Tick,Side`,
+/// loadable by Gephi) and, on exit, the flows summed per edge as a graphviz `.dot` file.
+///
+/// With `region_size` cells are grouped into square regions of that many cells per side,
+/// and only movements between regions are exported.
+pub struct FlowExportPlugin {
+    pub path: PathBuf,
+    pub region_size: Option<i32>,
+}
+
+impl Plugin for FlowExportPlugin {
+    fn build(&self, app: &mut App) {
+        let mut writer = match File::create(&self.path) {
+            Ok(file) => BufWriter::new(file),
+            Err(err) => {
+                error!("Flow export disabled, can't create {:?}: {err}", self.path);
+                return;
+            }
+        };
+        if let Err(err) = writeln!(writer, "Source,Target,Weight,Tick,Side") {
+            error!(
+                "Flow export disabled, can't write to {:?}: {err}",
+                self.path
+            );
+            return;
+        }
+
+        app.insert_resource(FlowExport {
+            writer,
+            dot_path: self.path.with_extension("dot"),
+            region_size: self.region_size,
+            tick: 0,
+            totals: HashMap::new(),
+        })
+        .add_systems(Update, export_flows.in_set(Bookkeeping))
+        .add_systems(Last, write_graph_on_exit);
+    }
+}
+
+#[derive(Resource)]
+struct FlowExport {
+    writer: BufWriter<File>,
+    dot_path: PathBuf,
+    region_size: Option<i32>,
+    tick: u64,
+    totals: HashMap<(String, String), i64>, // population moved per edge over the run
+}
+
+impl FlowExport {
+    fn node(&self, xy: IVec2) -> String {
+        match self.region_size {
+            Some(size) => format!("r{}_{}", xy.x.div_euclid(size), xy.y.div_euclid(size)),
+            None => format!("{}_{}", xy.x, xy.y),
+        }
+    }
+}
+
+fn export_flows(cells_q: Query<&ZombieState>, mut export: ResMut<FlowExport>) {
+    export.tick += 1;
+    let tick = export.tick;
+
+    // A cell sends its outflow to the neighbors on the next tick
+    let mut lines = String::new();
+    for state in cells_q.iter() {
+        let side = match state.status {
+            Status::Human | Status::Military => "human",
+            Status::Zombie => "zombie",
+            Status::Empty => continue,
+        };
+        for direction in 0..8 {
+            let moving = state.sent_toward(direction);
+            if moving == 0 {
+                continue;
+            }
+            let delta = direction_to_delta(direction).unwrap();
+            let (source, target) = (export.node(state.xy), export.node(state.xy + delta));
+            if source == target {
+                continue;
+            }
+            lines += &format!("{source},{target},{moving},{tick},{side}\n");
+            *export.totals.entry((source, target)).or_default() += moving as i64;
+        }
+    }
+
+    if let Err(err) = export.writer.write_all(lines.as_bytes()) {
+        warn!("Failed to write flows: {err}");
+    }
+}
+
+fn write_graph_on_exit(mut exit_events: EventReader<AppExit>, mut export: ResMut<FlowExport>) {
+    if exit_events.read().last().is_none() {
+        return;
+    }
+    if let Err(err) = export.writer.flush() {
+        warn!("Failed to flush flows: {err}");
+    }
+
+    let mut dot = String::from("digraph flows {\n");
+    for ((source, target), population) in &export.totals {
+        dot += &format!("  \"{source}\" -> \"{target}\" [weight={population}];\n");
+    }
+    dot += "}\n";
+    if let Err(err) = std::fs::write(&export.dot_path, dot) {
+        warn!("Failed to write flow graph: {err}");
+    }
+}
@@ -0,0 +1,172 @@
+use crate::chronicle::Headline;
+use crate::config::{Goal, MapConfig};
+use crate::photo::PhotoMode;
+use crate::stats::{WorldStats, sample_world_stats};
+use crate::tick::Bookkeeping;
+use crate::zombie_state::ZombieState;
+use bevy::prelude::*;
+
+/// Goals the map sets the humans, checked after every tick and listed in a panel in the
+/// top right corner with their progress. Maps without goals are a sandbox and show no panel.
+pub struct GoalsPlugin;
+
+impl Plugin for GoalsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_goals)
+            .add_systems(
+                Update,
+                check_goals.in_set(Bookkeeping).after(sample_world_stats),
+            )
+            .add_systems(Update, update_goals_panel);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GoalStatus {
+    #[default]
+    Pending,
+    Achieved,
+    Failed,
+}
+
+/// How far each of the map's goals has got, in their order.
+#[derive(Resource, Debug, Default)]
+pub struct GoalProgress(pub Vec<(Goal, GoalStatus, i64)>); // goal, status, ticks held or humans evacuated
+
+#[derive(Component)]
+struct GoalsPanel;
+
+#[derive(Component)]
+struct GoalsText;
+
+fn setup_goals(map: Res<MapConfig>, mut commands: Commands) {
+    commands.insert_resource(GoalProgress(
+        map.goals
+            .iter()
+            .map(|&goal| (goal, GoalStatus::Pending, 0))
+            .collect(),
+    ));
+    if map.goals.is_empty() {
+        return;
+    }
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                right: Val::Px(8.0),
+                padding: UiRect::all(Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            GoalsPanel,
+        ))
+        .with_child((Text::default(), TextFont::from_font_size(16.0), GoalsText));
+}
+
+fn check_goals(
+    stats: Res<WorldStats>,
+    cells_q: Query<&ZombieState>,
+    mut progress: ResMut<GoalProgress>,
+    mut headlines: EventWriter<Headline>,
+) {
+    let Some(latest) = stats.latest() else {
+        return;
+    };
+    let evacuated: i64 = cells_q
+        .iter()
+        .map(|state| state.ledger.evacuated as i64)
+        .sum();
+
+    for (goal, status, done) in progress.0.iter_mut() {
+        if *status != GoalStatus::Pending {
+            continue;
+        }
+        *status = match *goal {
+            Goal::Hold { min, max, ticks } => {
+                let held = cells_q
+                    .iter()
+                    .filter(|state| state.xy.cmpge(min).all() && state.xy.cmple(max).all())
+                    .all(|state| state.terrain.is_impassable() || state.status.is_living());
+                *done = if held { *done + 1 } else { 0 };
+                if *done >= ticks as i64 {
+                    GoalStatus::Achieved
+                } else {
+                    GoalStatus::Pending
+                }
+            }
+            Goal::Evacuate { humans } => {
+                *done += evacuated;
+                if *done >= humans {
+                    GoalStatus::Achieved
+                } else if latest.humans == 0 {
+                    GoalStatus::Failed
+                } else {
+                    GoalStatus::Pending
+                }
+            }
+            Goal::Eradicate { before_tick } => {
+                if latest.zombies == 0 {
+                    GoalStatus::Achieved
+                } else if latest.tick >= before_tick {
+                    GoalStatus::Failed
+                } else {
+                    GoalStatus::Pending
+                }
+            }
+        };
+        match *status {
+            GoalStatus::Achieved => {
+                headlines.send(Headline(format!("goal achieved: {}", describe(goal))))
+            }
+            GoalStatus::Failed => {
+                headlines.send(Headline(format!("goal failed: {}", describe(goal))))
+            }
+            GoalStatus::Pending => continue,
+        };
+    }
+}
+
+fn describe(goal: &Goal) -> String {
+    match *goal {
+        Goal::Hold { min, max, ticks } => format!("hold {min} to {max} for {ticks} ticks"),
+        Goal::Evacuate { humans } => format!("evacuate {humans} humans"),
+        Goal::Eradicate { before_tick } => {
+            format!("eradicate the zombies before tick {before_tick}")
+        }
+    }
+}
+
+fn update_goals_panel(
+    progress: Res<GoalProgress>,
+    photo: Option<Res<PhotoMode>>,
+    mut text_q: Query<&mut Text, With<GoalsText>>,
+    mut panel_q: Query<&mut Visibility, With<GoalsPanel>>,
+) {
+    if progress.is_changed() {
+        let lines: Vec<String> = progress
+            .0
+            .iter()
+            .map(|(goal, status, done)| {
+                let state = match (status, goal) {
+                    (GoalStatus::Achieved, _) => "done".to_string(),
+                    (GoalStatus::Failed, _) => "failed".to_string(),
+                    (GoalStatus::Pending, Goal::Hold { ticks, .. }) => format!("{done}/{ticks}"),
+                    (GoalStatus::Pending, Goal::Evacuate { humans }) => format!("{done}/{humans}"),
+                    (GoalStatus::Pending, Goal::Eradicate { .. }) => "pending".to_string(),
+                };
+                format!("{} ({state})", describe(goal))
+            })
+            .collect();
+        for mut text in text_q.iter_mut() {
+            text.0 = format!("Goals\n{}", lines.join("\n"));
+        }
+    }
+    for mut visibility in panel_q.iter_mut() {
+        // Kept out of the photos, like the HUD
+        visibility.set_if_neq(match photo {
+            Some(_) => Visibility::Hidden,
+            None => Visibility::Inherited,
+        });
+    }
+}
@@ -0,0 +1,74 @@
+use crate::config::{MapConfig, SimulationConfig};
+use crate::rng::SimRng;
+use crate::save::{self, SaveFile};
+use crate::tick::Bookkeeping;
+use crate::{
+    SimulationPlugins, WorldSeed, chronicle, generate_terrain, outcome, report, spawn_cells,
+    stats_export,
+};
+use bevy::app::TerminalCtrlCHandlerPlugin;
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use bevy_life::SimulationBatch;
+use std::path::PathBuf;
+
+/// `--headless <ticks>` mode: runs the simulation rules without a window or any views,
+/// one tick per update as fast as possible, then prints the final report and exits.
+/// With a `save` (its own `map` along) the run resumes from it, like the game does.
+pub fn run(
+    ticks: u64,
+    map: MapConfig,
+    rules: SimulationConfig,
+    save: Option<SaveFile>,
+    report: Option<PathBuf>,
+    stats_path: Option<PathBuf>,
+) {
+    println!("Headless run of {ticks} ticks, seed {}", map.seed);
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, LogPlugin::default()))
+        .insert_resource(rules)
+        .add_plugins(SimulationPlugins {
+            tick_time_step: None, // a tick on every update, however long it took
+        })
+        .insert_resource(SimulationBatch)
+        .insert_resource(WorldSeed(map.seed))
+        .insert_resource(SimRng::new(map.seed))
+        .insert_resource(map)
+        .insert_resource(TicksLeft(ticks))
+        .add_systems(
+            Startup,
+            (
+                generate_terrain,
+                save::resume_rules.run_if(resource_exists::<SaveFile>),
+                spawn_cells,
+            )
+                .chain(),
+        )
+        .add_plugins((
+            outcome::OutcomePlugin { exit: true },
+            chronicle::ChroniclePlugin { path: None },
+            report::FinalReportPlugin { path: report },
+        ))
+        .add_systems(Update, count_down.in_set(Bookkeeping));
+    if let Some(save) = save {
+        app.insert_resource(save);
+    }
+    if let Some(path) = stats_path {
+        app.add_plugins(stats_export::StatsExportPlugin { path });
+    }
+    if !app.is_plugin_added::<TerminalCtrlCHandlerPlugin>() {
+        app.add_plugins(TerminalCtrlCHandlerPlugin);
+    }
+    app.run();
+}
+
+#[derive(Resource)]
+struct TicksLeft(u64);
+
+fn count_down(mut ticks_left: ResMut<TicksLeft>, mut exit: EventWriter<AppExit>) {
+    ticks_left.0 = ticks_left.0.saturating_sub(1);
+    if ticks_left.0 == 0 {
+        exit.send(AppExit::Success);
+    }
+}
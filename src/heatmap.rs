@@ -0,0 +1,99 @@
+use crate::config::MapConfig;
+use crate::help::KeybindingsAppExt;
+use crate::loading::LoadingState;
+use crate::overlays::{Overlays, map_image, map_sprite};
+use crate::zombie_state::ZombieState;
+use bevy::prelude::*;
+
+const EXPORT_PATH: &str = "contestation.png";
+
+/// "Bloodiest ground" map: how many times each cell changed hands, shown as an overlay (B)
+/// and saved as an image with Shift+B.
+pub struct ContestationHeatmapPlugin;
+
+impl Plugin for ContestationHeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_keybindings(
+            "Overlays",
+            &[("Shift+B", "save the contestation heatmap image")],
+        )
+        .add_systems(OnEnter(LoadingState::BuildingViews), setup_heatmap)
+        .add_systems(
+            Update,
+            (update_heatmap, export_heatmap)
+                .chain()
+                .run_if(in_state(LoadingState::Ready)),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct Heatmap(Handle<Image>);
+
+#[derive(Component)]
+struct HeatmapView;
+
+fn setup_heatmap(map: Res<MapConfig>, mut images: ResMut<Assets<Image>>, mut commands: Commands) {
+    let image = images.add(map_image(map.width, map.height, |_, _| [0, 0, 0, 0]));
+    commands.spawn((
+        map_sprite(&map, image.clone(), 4.0),
+        Visibility::Hidden,
+        HeatmapView,
+    ));
+    commands.insert_resource(Heatmap(image));
+}
+
+fn heatmap_image(map: &MapConfig, cells_q: &Query<&ZombieState>) -> Image {
+    let mut captures = vec![0; map.width * map.height];
+    for state in cells_q.iter() {
+        captures[state.xy.y as usize * map.width + state.xy.x as usize] = state.captures;
+    }
+    let max = captures.iter().copied().max().unwrap_or(0).max(1) as f32;
+
+    // From transparent to opaque dark red, square root so rarely contested ground still shows
+    map_image(map.width, map.height, |x, y| {
+        let heat = (captures[y * map.width + x] as f32 / max).sqrt();
+        [(90.0 + heat * 165.0) as u8, 0, 0, (heat * 220.0) as u8]
+    })
+}
+
+fn update_heatmap(
+    map: Res<MapConfig>,
+    overlays: Res<Overlays>,
+    cells_q: Query<&ZombieState>,
+    heatmap: Res<Heatmap>,
+    mut images: ResMut<Assets<Image>>,
+    mut view_q: Query<&mut Visibility, With<HeatmapView>>,
+) {
+    for mut visibility in view_q.iter_mut() {
+        *visibility = if overlays.contestation {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+    if overlays.contestation
+        && let Some(image) = images.get_mut(&heatmap.0)
+    {
+        *image = heatmap_image(&map, &cells_q);
+    }
+}
+
+fn export_heatmap(
+    keys: Res<ButtonInput<KeyCode>>,
+    map: Res<MapConfig>,
+    cells_q: Query<&ZombieState>,
+) {
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if !shift || !keys.just_pressed(KeyCode::KeyB) {
+        return;
+    }
+
+    match heatmap_image(&map, &cells_q).try_into_dynamic() {
+        Ok(image) => match image.save(EXPORT_PATH) {
+            Ok(()) => info!("Contestation heatmap saved to {EXPORT_PATH}"),
+            Err(err) => error!("Failed to save contestation heatmap: {err}"),
+        },
+        Err(err) => error!("Failed to convert contestation heatmap: {err}"),
+    }
+}
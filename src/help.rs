@@ -0,0 +1,128 @@
+use crate::photo::PhotoMode;
+use bevy::prelude::*;
+
+/// Help overlay (H) listing the keybindings plugins registered with
+/// [`KeybindingsAppExt::add_keybindings`], grouped by the plugin that handles them.
+pub struct HelpPlugin;
+
+impl Plugin for HelpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_keybindings("Help", &[("H", "show or hide this help")])
+            .add_systems(Startup, setup_help)
+            .add_systems(Update, (toggle_help, update_help).chain());
+    }
+}
+
+/// Every keybinding registered so far, in registration order.
+#[derive(Resource, Default, Debug)]
+pub struct Keybindings(Vec<Keybinding>);
+
+#[derive(Clone, Copy, Debug)]
+pub struct Keybinding {
+    pub group: &'static str,
+    pub keys: &'static str,
+    pub action: &'static str,
+}
+
+impl Keybindings {
+    /// Registered groups in order, each with its bindings.
+    pub fn groups(&self) -> Vec<(&'static str, Vec<Keybinding>)> {
+        let mut groups: Vec<(&'static str, Vec<Keybinding>)> = Vec::new();
+        for binding in &self.0 {
+            match groups.iter_mut().find(|(group, _)| *group == binding.group) {
+                Some((_, bindings)) => bindings.push(*binding),
+                None => groups.push((binding.group, vec![*binding])),
+            }
+        }
+        groups
+    }
+}
+
+pub trait KeybindingsAppExt {
+    /// Registers the `(keys, action)` bindings a plugin handles, under its `group` name.
+    /// Called from the plugin's `build`, next to the systems reading the keys.
+    fn add_keybindings(
+        &mut self,
+        group: &'static str,
+        bindings: &[(&'static str, &'static str)],
+    ) -> &mut Self;
+}
+
+impl KeybindingsAppExt for App {
+    fn add_keybindings(
+        &mut self,
+        group: &'static str,
+        bindings: &[(&'static str, &'static str)],
+    ) -> &mut Self {
+        self.init_resource::<Keybindings>();
+        let mut registry = self.world_mut().resource_mut::<Keybindings>();
+        for &(keys, action) in bindings {
+            registry.0.push(Keybinding {
+                group,
+                keys,
+                action,
+            });
+        }
+        self
+    }
+}
+
+#[derive(Component)]
+struct HelpPanel;
+
+fn setup_help(keybindings: Res<Keybindings>, mut commands: Commands) {
+    let mut text = String::new();
+    for (group, bindings) in keybindings.groups() {
+        text.push_str(&format!("{group}\n"));
+        for binding in bindings {
+            text.push_str(&format!("  {:<12} {}\n", binding.keys, binding.action));
+        }
+    }
+
+    commands.spawn((
+        Text(text.trim_end().to_string()),
+        TextFont::from_font_size(14.0),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Percent(35.0),
+            padding: UiRect::all(Val::Px(10.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+        Visibility::Hidden,
+        HelpPanel,
+    ));
+}
+
+/// Present while the help is shown.
+#[derive(Resource)]
+struct HelpShown;
+
+fn toggle_help(
+    keys: Res<ButtonInput<KeyCode>>,
+    shown: Option<Res<HelpShown>>,
+    mut commands: Commands,
+) {
+    if !keys.just_pressed(KeyCode::KeyH) {
+        return;
+    }
+    match shown {
+        None => commands.insert_resource(HelpShown),
+        Some(_) => commands.remove_resource::<HelpShown>(),
+    }
+}
+
+fn update_help(
+    shown: Option<Res<HelpShown>>,
+    photo: Option<Res<PhotoMode>>,
+    mut panel_q: Query<&mut Visibility, With<HelpPanel>>,
+) {
+    for mut visibility in panel_q.iter_mut() {
+        // Kept out of the photos
+        visibility.set_if_neq(match (&shown, &photo) {
+            (Some(_), None) => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        });
+    }
+}
@@ -0,0 +1,230 @@
+use crate::config::SimulationConfig;
+use crate::cure::CureProgress;
+use crate::loading::LoadingState;
+use crate::overlays::map_image;
+use crate::photo::PhotoMode;
+use crate::profiler;
+use crate::satellite::SatelliteView;
+use crate::stats::{TickStats, WorldStats};
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+
+const CHART_TICKS: usize = 200; // one column per tick, the newest on the right
+const CHART_HEIGHT: usize = 60;
+const STAGE_BAR_HEIGHT: usize = 6;
+// Cell update, bookkeeping and hooks, as in profiler::STAGES
+const STAGE_COLORS: [[u8; 4]; 3] = [
+    [220, 160, 40, 255],
+    [80, 170, 200, 255],
+    [190, 90, 190, 255],
+];
+
+/// Panel in the top left corner with the tick, both populations, the infection rate, the cure,
+/// FPS and the time a tick takes (split by stage into a bar when the stage profiler is on),
+/// above a stacked area chart of the share of the land each side controls and a minimap.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        app.add_systems(Startup, setup_hud)
+            .add_systems(OnEnter(LoadingState::Ready), add_minimap)
+            .add_systems(Update, (update_hud, update_control_chart, update_stage_bar));
+    }
+}
+
+#[derive(Component)]
+struct Hud;
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Resource)]
+struct ControlChart(Handle<Image>);
+
+#[derive(Resource)]
+struct StageBar(Handle<Image>);
+
+fn setup_hud(mut images: ResMut<Assets<Image>>, mut commands: Commands) {
+    let chart = images.add(control_chart_image(&[]));
+    let stage_bar = images.add(stage_bar_image(&[]));
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                left: Val::Px(8.0),
+                padding: UiRect::all(Val::Px(6.0)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            Hud,
+        ))
+        .with_children(|hud| {
+            hud.spawn((Text::default(), TextFont::from_font_size(16.0), HudText));
+            hud.spawn((
+                ImageNode::new(stage_bar.clone()),
+                Node {
+                    width: Val::Px(CHART_TICKS as f32),
+                    height: Val::Px(STAGE_BAR_HEIGHT as f32),
+                    ..default()
+                },
+            ));
+            hud.spawn((
+                ImageNode::new(chart.clone()),
+                Node {
+                    width: Val::Px(CHART_TICKS as f32),
+                    height: Val::Px(CHART_HEIGHT as f32),
+                    ..default()
+                },
+            ));
+        });
+    commands.insert_resource(ControlChart(chart));
+    commands.insert_resource(StageBar(stage_bar));
+}
+
+/// The satellite view under the chart, as wide as it.
+fn add_minimap(
+    satellite: Option<Res<SatelliteView>>,
+    hud_q: Query<Entity, With<Hud>>,
+    mut commands: Commands,
+) {
+    let (Some(satellite), Ok(hud)) = (satellite, hud_q.get_single()) else {
+        return;
+    };
+    let width = CHART_TICKS as f32;
+    let aspect = satellite.size.y as f32 / satellite.size.x.max(1) as f32;
+    commands.entity(hud).with_child((
+        ImageNode::new(satellite.image.clone()),
+        Node {
+            width: Val::Px(width),
+            height: Val::Px(width * aspect),
+            ..default()
+        },
+    ));
+}
+
+fn update_hud(
+    stats: Res<WorldStats>,
+    diagnostics: Res<DiagnosticsStore>,
+    cure: Option<Res<CureProgress>>,
+    rules: Res<SimulationConfig>,
+    photo: Option<Res<PhotoMode>>,
+    mut text_q: Query<&mut Text, With<HudText>>,
+    mut hud_q: Query<&mut Visibility, With<Hud>>,
+) {
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(0.0);
+    let latest = stats.latest().copied().unwrap_or_default();
+    let (human_control, zombie_control, neutral_control) = latest.control_percent();
+    let cure = match cure {
+        Some(cure) if cure.is_ready(&rules) => "Cure ready\n".to_string(),
+        Some(cure) => format!("Cure {:.0}%, {} labs\n", cure.percent(&rules), cure.labs),
+        None => String::new(),
+    };
+    let stages = stage_times(&diagnostics);
+    let tick_time = match stages.as_slice() {
+        [cells, bookkeeping, hooks] => format!(
+            "\nTick {:.2} ms: cells {cells:.2}, bookkeeping {bookkeeping:.2}, hooks {hooks:.2}",
+            cells + bookkeeping + hooks
+        ),
+        _ => String::new(),
+    };
+
+    for mut text in text_q.iter_mut() {
+        text.0 = format!(
+            "Tick {}\nHumans {}\nZombies {}\nInfected {} per tick\nSecured {} cells\n\
+             Land {human_control:.0}% humans, {zombie_control:.0}% zombies, {neutral_control:.0}% neutral\n\
+             {cure}FPS {fps:.0}{tick_time}",
+            latest.tick, latest.humans, latest.zombies, latest.conversions, latest.secured_cells
+        );
+    }
+    for mut visibility in hud_q.iter_mut() {
+        // Kept out of the photos
+        visibility.set_if_neq(match photo {
+            Some(_) => Visibility::Hidden,
+            None => Visibility::Inherited,
+        });
+    }
+}
+
+fn update_control_chart(
+    stats: Res<WorldStats>,
+    chart: Res<ControlChart>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !stats.is_changed() {
+        return;
+    }
+    if let Some(image) = images.get_mut(&chart.0) {
+        *image = control_chart_image(stats.recent(CHART_TICKS));
+    }
+}
+
+/// Smoothed ms per tick of each profiled stage, empty without the stage profiler.
+fn stage_times(diagnostics: &DiagnosticsStore) -> Vec<f64> {
+    profiler::STAGES
+        .iter()
+        .map(|path| diagnostics.get(path).and_then(|stage| stage.smoothed()))
+        .collect::<Option<Vec<f64>>>()
+        .unwrap_or_default()
+}
+
+fn update_stage_bar(
+    diagnostics: Res<DiagnosticsStore>,
+    bar: Res<StageBar>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let stages = stage_times(&diagnostics);
+    if stages.is_empty() {
+        return;
+    }
+    if let Some(image) = images.get_mut(&bar.0) {
+        *image = stage_bar_image(&stages);
+    }
+}
+
+/// The stages side by side, each as wide as its share of the tick.
+fn stage_bar_image(stages: &[f64]) -> Image {
+    let total: f64 = stages.iter().sum();
+    map_image(CHART_TICKS, STAGE_BAR_HEIGHT, |x, _| {
+        if total <= 0.0 {
+            return [0, 0, 0, 0];
+        }
+        let at = (x as f64 + 0.5) / CHART_TICKS as f64 * total;
+        let mut end = 0.0;
+        for (stage, color) in stages.iter().zip(STAGE_COLORS) {
+            end += stage;
+            if at < end {
+                return color;
+            }
+        }
+        [0, 0, 0, 0]
+    })
+}
+
+/// Humans stacked under zombies under neutral ground, one column per tick.
+fn control_chart_image(history: &[TickStats]) -> Image {
+    // Right aligned, so the chart scrolls left as the ticks come in
+    let offset = CHART_TICKS - history.len().min(CHART_TICKS);
+    map_image(CHART_TICKS, CHART_HEIGHT, |x, y| {
+        let Some(sample) = x.checked_sub(offset).and_then(|i| history.get(i)) else {
+            return [0, 0, 0, 0];
+        };
+        let (humans, zombies, _) = sample.control_percent();
+        let level = (y as f32 + 0.5) * 100.0 / CHART_HEIGHT as f32;
+        if level < humans {
+            [65, 105, 225, 255] // royal blue, as the human cells
+        } else if level < humans + zombies {
+            [0, 128, 0, 255] // green, as the zombie cells
+        } else {
+            [110, 110, 110, 255]
+        }
+    })
+}
@@ -0,0 +1,74 @@
+use crate::photo::PhotoMode;
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+use bevy::winit::{UpdateMode, WinitSettings};
+use std::time::Duration;
+
+// Updates per second while unfocused, only to keep ticking and to notice the focus coming back
+const UNFOCUSED_WAKE: Duration = Duration::from_millis(100);
+const SLOW_SPEED: f64 = 0.2; // simulation speed while unfocused with IdleBehavior::Slow
+
+/// What the simulation does while the window is out of focus, rendering stops in any case.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdleBehavior {
+    Run, // keep ticking at full speed
+    #[default]
+    Slow,
+    Pause,
+}
+
+/// Throttles the app while the window is unfocused or minimized: the camera stops rendering,
+/// the app wakes up only every `UNFOCUSED_WAKE`, and the simulation slows down or pauses.
+///
+/// With `focused_wake` the focused window saves power too (desktop mode): instead of
+/// redrawing continuously it only updates on input or once per `focused_wake`, i.e. per tick.
+pub struct IdlePlugin {
+    pub behavior: IdleBehavior,
+    pub focused_wake: Option<Duration>,
+}
+
+impl Plugin for IdlePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WinitSettings {
+            focused_mode: match self.focused_wake {
+                Some(wake) => UpdateMode::reactive_low_power(wake),
+                None => UpdateMode::Continuous,
+            },
+            unfocused_mode: UpdateMode::reactive_low_power(UNFOCUSED_WAKE),
+        })
+        .insert_resource(Idle(self.behavior))
+        .add_systems(PreUpdate, throttle_when_unfocused);
+    }
+}
+
+#[derive(Resource)]
+struct Idle(IdleBehavior);
+
+fn throttle_when_unfocused(
+    mut focus_events: EventReader<WindowFocused>,
+    idle: Res<Idle>,
+    photo: Option<Res<PhotoMode>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut cameras_q: Query<&mut Camera>,
+) {
+    let Some(focused) = focus_events.read().last().map(|event| event.focused) else {
+        return;
+    };
+
+    for mut camera in cameras_q.iter_mut() {
+        camera.is_active = focused;
+    }
+    match idle.0 {
+        IdleBehavior::Run => {}
+        IdleBehavior::Slow => time.set_relative_speed_f64(if focused { 1.0 } else { SLOW_SPEED }),
+        // Photo mode keeps the simulation paused itself
+        IdleBehavior::Pause if photo.is_none() => {
+            if focused {
+                time.unpause();
+            } else {
+                time.pause();
+            }
+        }
+        IdleBehavior::Pause => {}
+    }
+}
@@ -0,0 +1,187 @@
+use crate::CELL_SIZE;
+use crate::config::MapConfig;
+use crate::help::KeybindingsAppExt;
+use crate::overlays::{cell_to_world, world_to_cell};
+use crate::paint::PaintMode;
+use crate::photo::PhotoMode;
+use crate::zombie_state::{SMELL_SCALE, ZombieState};
+use bevy::color::palettes::css::WHITE;
+use bevy::prelude::*;
+
+/// Cell inspector: clicking a cell shows its full state in a panel on the right,
+/// updated every frame. Right click or Escape closes it.
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_keybindings(
+            "Inspector",
+            &[
+                ("Left click", "inspect a cell"),
+                ("Right click", "close the inspector"),
+                ("Escape", "close the inspector"),
+            ],
+        )
+        .init_resource::<Inspected>()
+        .add_systems(Startup, setup_inspector)
+        .add_systems(
+            Update,
+            (
+                select_cell.run_if(
+                    not(resource_exists::<PhotoMode>).and(not(resource_exists::<PaintMode>)),
+                ),
+                update_inspector,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Cell shown in the inspector.
+#[derive(Resource, Default)]
+struct Inspected(Option<Entity>);
+
+#[derive(Component)]
+struct InspectorPanel;
+
+fn setup_inspector(mut commands: Commands) {
+    commands.spawn((
+        Text::default(),
+        TextFont::from_font_size(14.0),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(32.0),
+            right: Val::Px(8.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Visibility::Hidden,
+        InspectorPanel,
+    ));
+}
+
+fn select_cell(
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    map: Res<MapConfig>,
+    windows_q: Query<&Window>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    cells_q: Query<(Entity, &ZombieState)>,
+    mut inspected: ResMut<Inspected>,
+) {
+    if mouse.just_pressed(MouseButton::Right) || keys.just_pressed(KeyCode::Escape) {
+        inspected.0 = None;
+        return;
+    }
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(xy) = cell_under_cursor(&map, &windows_q, &camera_q) else {
+        return;
+    };
+    inspected.0 = cells_q
+        .iter()
+        .find(|(_, state)| state.xy == xy)
+        .map(|(cell, _)| cell);
+}
+
+/// Cell the mouse is over, if it's over the map.
+pub fn cell_under_cursor(
+    map: &MapConfig,
+    windows_q: &Query<&Window>,
+    camera_q: &Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+) -> Option<IVec2> {
+    let (window, (camera, camera_tf)) = (windows_q.get_single().ok()?, camera_q.get_single().ok()?);
+    let world = camera
+        .viewport_to_world_2d(camera_tf, window.cursor_position()?)
+        .ok()?;
+    world_to_cell(map, world)
+}
+
+fn update_inspector(
+    inspected: Res<Inspected>,
+    map: Res<MapConfig>,
+    photo: Option<Res<PhotoMode>>,
+    cells_q: Query<&ZombieState>,
+    mut panel_q: Query<(&mut Text, &mut Visibility), With<InspectorPanel>>,
+    mut gizmos: Gizmos,
+) {
+    let state = inspected.0.and_then(|cell| cells_q.get(cell).ok());
+    for (mut text, mut visibility) in panel_q.iter_mut() {
+        let Some(state) = state.filter(|_| photo.is_none()) else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+        text.0 = describe(state);
+    }
+
+    if let Some(state) = state.filter(|_| photo.is_none()) {
+        gizmos.rect_2d(
+            Isometry2d::from_translation(cell_to_world(&map, state.xy.as_vec2())),
+            Vec2::splat(CELL_SIZE),
+            WHITE,
+        );
+    }
+}
+
+fn describe(state: &ZombieState) -> String {
+    let ledger = &state.ledger;
+    format!(
+        "Cell {}\n\
+         status: {:?}, population {} ({} infected)\n\
+         direction: {}, outflow: {:?}\n\
+         smell: human {}, zombie {} (seen {})\n\
+         noise: {}, panic: {} from {}\n\
+         {:?} {:?}, altitude: {}, temperature: {}, moisture: {}\n\
+         lineage: {}, captures: {}, corpses: {}, food: {}\n\
+         camp: {}, chokepoint: {}, hiding: {}, fortification: {}\n\
+         supplied: {}, encircled: {} ticks, secured: {} ({} ticks)\n\
+         objective: {:?}, shamble: {}, calm: {} ticks\n\
+         last tick: +{} born, -{} human, -{} zombie, {} turned,\n\
+         -{} disease, -{} decayed, -{} starved, -{} stragglers, -{} emigrated, -{} famine",
+        state.xy,
+        state.status,
+        state.population,
+        state.infected(),
+        state.direction,
+        state.outflow,
+        state.smell_human / SMELL_SCALE,
+        state.smell_zombie / SMELL_SCALE,
+        state.intel_zombie / SMELL_SCALE,
+        state.noise,
+        state.panic,
+        state.panic_source,
+        state.terrain,
+        state.biome,
+        state.altitude,
+        state.temperature,
+        state.moisture,
+        state.lineage,
+        state.captures,
+        state.corpses,
+        state.food,
+        state.camp,
+        state.chokepoint,
+        state.hiding,
+        state.fortification,
+        state.supplied,
+        state.encircled,
+        state.secured,
+        state.secure_ticks,
+        state.objective,
+        state.shamble,
+        state.calm_ticks,
+        ledger.births,
+        ledger.human_deaths,
+        ledger.zombie_deaths,
+        ledger.conversions,
+        ledger.disease_deaths,
+        ledger.decayed,
+        ledger.starved,
+        ledger.stragglers,
+        ledger.emigrated,
+        ledger.famine,
+    )
+}
@@ -0,0 +1,21 @@
+//! The zombie apocalypse rules as a [`bevy_life`] cell state, without anything drawn.
+//!
+//! Add [`ZombiePlugin`] and [`config::SimulationConfigPlugin`], then spawn a
+//! [`zombie_state::ZombieState`] (see [`zombie_state::ZombieStateBuilder`]) with a
+//! [`boundary::GridCell`] for every cell of the map. Each cell carries the
+//! [`config::SimulationConfig`] it's updated with as its [`config::Rules`], there's no global
+//! config, so apps side by side can each run with their own.
+//!
+//! The game and its headless runs are built on top of this, depend on the crate with
+//! `default-features = false` to leave the game's window and rendering out.
+
+pub mod boundary;
+pub mod config;
+pub mod error;
+pub mod rng;
+pub mod terrain;
+pub mod zombie_state;
+
+use bevy_life::CellularAutomatonPlugin;
+
+pub type ZombiePlugin = CellularAutomatonPlugin<boundary::GridCell, zombie_state::ZombieState>;
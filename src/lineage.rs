@@ -0,0 +1,79 @@
+use crate::config::MapConfig;
+use crate::loading::LoadingState;
+use crate::overlays::{Overlays, map_image, map_sprite};
+use crate::zombie_state::ZombieState;
+use bevy::color::ColorToPacked;
+use bevy::prelude::*;
+
+/// Overlay (O) coloring every horde by the outbreak it descends from
+/// and every human cell by its founding settlement.
+pub struct LineagePlugin;
+
+impl Plugin for LineagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(LoadingState::BuildingViews), setup_lineage_view)
+            .add_systems(
+                Update,
+                update_lineage_view.run_if(in_state(LoadingState::Ready)),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct LineageImage(Handle<Image>);
+
+#[derive(Component)]
+struct LineageView;
+
+fn setup_lineage_view(
+    map: Res<MapConfig>,
+    mut images: ResMut<Assets<Image>>,
+    mut commands: Commands,
+) {
+    let image = images.add(map_image(map.width, map.height, |_, _| [0, 0, 0, 0]));
+    commands.spawn((
+        map_sprite(&map, image.clone(), 4.5),
+        Visibility::Hidden,
+        LineageView,
+    ));
+    commands.insert_resource(LineageImage(image));
+}
+
+/// Distinct, stable color for a lineage, hues spread by the golden angle.
+pub fn lineage_color(lineage: u32) -> [u8; 4] {
+    if lineage == 0 {
+        return [0, 0, 0, 0];
+    }
+    let hue = (lineage as f32 * 137.508) % 360.0;
+    Color::hsla(hue, 0.85, 0.55, 0.85).to_srgba().to_u8_array()
+}
+
+fn update_lineage_view(
+    map: Res<MapConfig>,
+    overlays: Res<Overlays>,
+    cells_q: Query<&ZombieState>,
+    lineage_image: Res<LineageImage>,
+    mut images: ResMut<Assets<Image>>,
+    mut view_q: Query<&mut Visibility, With<LineageView>>,
+) {
+    for mut visibility in view_q.iter_mut() {
+        *visibility = if overlays.lineage {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+    if !overlays.lineage {
+        return;
+    }
+
+    let mut lineages = vec![0; map.width * map.height];
+    for state in cells_q.iter() {
+        lineages[state.xy.y as usize * map.width + state.xy.x as usize] = state.lineage;
+    }
+    if let Some(image) = images.get_mut(&lineage_image.0) {
+        *image = map_image(map.width, map.height, |x, y| {
+            lineage_color(lineages[y * map.width + x])
+        });
+    }
+}
@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+
+/// Startup pipeline of the windowed app, every step runs on entering its state.
+/// Each step moves on to the next one on the following frame, so it sees the
+/// entities and resources the previous step created.
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum LoadingState {
+    #[default]
+    GeneratingTerrain, // or reading it from the save
+    SpawningCells,
+    BuildingViews, // meshes, materials and overlay images
+    Ready,
+}
+
+impl LoadingState {
+    fn next(self) -> Option<LoadingState> {
+        match self {
+            LoadingState::GeneratingTerrain => Some(LoadingState::SpawningCells),
+            LoadingState::SpawningCells => Some(LoadingState::BuildingViews),
+            LoadingState::BuildingViews => Some(LoadingState::Ready),
+            LoadingState::Ready => None,
+        }
+    }
+}
+
+/// Runs [`LoadingState`], systems that need the views gate on `in_state(LoadingState::Ready)`.
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<LoadingState>().add_systems(
+            Update,
+            advance_loading.run_if(not(in_state(LoadingState::Ready))),
+        );
+    }
+}
+
+fn advance_loading(state: Res<State<LoadingState>>, mut next: ResMut<NextState<LoadingState>>) {
+    if let Some(step) = state.get().next() {
+        info!("Loading: {step:?}");
+        next.set(step);
+    }
+}
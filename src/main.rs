@@ -1,238 +1,459 @@
-mod terrain;
-mod zombie_state;
-
-use crate::zombie_state::{Status, ZombieState};
-use bevy::color::palettes::css::*;
+#[cfg(feature = "gui")]
+mod cell_views;
+#[cfg(feature = "gui")]
+mod checksum;
+#[cfg(feature = "gui")]
+mod chokepoints;
+mod chronicle;
+#[cfg(feature = "gui")]
+mod contours;
+#[cfg(feature = "gui")]
+mod corpses;
+mod cure;
+mod daynight;
+#[cfg(feature = "exporters")]
+mod flows;
+#[cfg(feature = "gui")]
+mod goals;
+mod headless;
+#[cfg(feature = "gui")]
+mod heatmap;
+#[cfg(feature = "gui")]
+mod help;
+#[cfg(feature = "gui")]
+mod hud;
+#[cfg(feature = "gui")]
+mod idle;
+#[cfg(feature = "dev_tools")]
+mod inspector;
+#[cfg(feature = "gui")]
+mod lineage;
+#[cfg(feature = "gui")]
+mod loading;
+mod outcome;
+#[cfg(feature = "gui")]
+mod overlays;
+#[cfg(feature = "dev_tools")]
+mod paint;
+#[cfg(feature = "gui")]
+mod photo;
+#[cfg(feature = "gui")]
+mod profiler;
+#[cfg(feature = "gui")]
+mod quality;
+mod random_events;
+mod report;
+#[cfg(feature = "gui")]
+mod satellite;
+mod save;
+mod seasons;
+#[cfg(feature = "gui")]
+mod secured;
+mod settlements;
+#[cfg(feature = "gui")]
+mod smell;
+mod soak;
+#[cfg(feature = "gui")]
+mod speed;
+mod stats;
+mod stats_export;
+mod strategy;
+mod supply;
+#[cfg(feature = "exporters")]
+mod telemetry;
+#[cfg(feature = "gui")]
+mod terrain_layers;
+mod tick;
+#[cfg(feature = "gui")]
+mod toast;
+#[cfg(feature = "gui")]
+mod vector_field;
+#[cfg(feature = "exporters")]
+mod video;
+mod weather;
+
+use crate::config::MapConfig;
+#[cfg(feature = "gui")]
+use crate::loading::LoadingState;
+use crate::rng::SimRng;
+use crate::zombie_state::ZombieState;
+use bevy::app::PluginGroupBuilder;
 use bevy::prelude::*;
-use bevy_life::CellularAutomatonPlugin;
-use bevy_life::{LifeSystemSet, MooreCell2d, SimulationBatch};
-
-pub type ZombiePlugin = CellularAutomatonPlugin<MooreCell2d, ZombieState>;
+#[cfg(feature = "gui")]
+use bevy_life::SimulationBatch;
+use bevy_zombie_test::{ZombiePlugin, boundary, config, error, rng, terrain, zombie_state};
+#[cfg(feature = "gui")]
+use std::time::Duration;
 
 const SCALE: i32 = 100;
 
-fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Zombie Test".to_string(),
-                resolution: (1900.0, 1100.0).into(),
-                ..default()
-            }),
-            ..default()
-        }))
-        .add_plugins(ZombiePlugin {
-            tick_time_step: Some(0.1),
-            ..default()
-        })
-        .insert_resource(SimulationBatch)
-        .add_systems(Startup, (setup_camera, setup_map))
-        .add_systems(PostStartup, (setup_assets, setup_views).chain())
-        .add_systems(
-            Update,
-            (update_cell_views, state_debug).after(LifeSystemSet::CellUpdate),
-        )
-        .run();
-}
-
-fn setup_camera(mut commands: Commands) {
-    commands.spawn(Camera2d);
-}
+#[cfg(feature = "gui")]
+// Seconds between redraws in desktop mode when ticks aren't timed
+const DESKTOP_WAKE_UNTIMED: f64 = 0.1;
 
-const CELL_SIZE: f32 = 12.0;
-const CELL_HALF_SIZE: f32 = CELL_SIZE / 2.0;
-
-fn setup_map(mut commands: Commands) {
-    let (size_x, size_y) = (150, 75);
-    let terrain = terrain::TerrainGenerator::new(42).generate(size_x, size_y, 5, 100.0);
-
-    commands
-        .spawn((Transform::from_xyz(
-            -(size_x as f32 * CELL_SIZE) / 2.,
-            -(size_y as f32 * CELL_SIZE) / 2.,
-            0.,
-        ),))
-        .with_children(|builder| {
-            for y in 0..size_y {
-                for x in 0..size_x {
-                    let mut gen_at_location: Vec<i32> = vec![0; 9];
-                    gen_at_location[0] = x as i32; // X coordinate
-                    gen_at_location[1] = y as i32; // Y coordinate
-                    gen_at_location[2] = terrain[y][x][0] as i32; // Altitude
-                    gen_at_location[3] = terrain[y][x][1] as i32; // Temperature
-
-                    // Temporary, randomly assign cells as human, zombie, empty, and with population
-                    let random_state = rand::random::<u8>() % 4; // Randomly choose between 0-3
-                    gen_at_location[4] = match random_state {
-                        0 => 0, // Empty
-                        1 => 1, // Zombie
-                        2 => 2, // Human
-                        _ => 0, // Default to empty
-                    };
-                    // If human, give a big population. If zombie, a small one.
-                    gen_at_location[5] = if gen_at_location[4] == 2 {
-                        (rand::random::<u8>() % 100 + 50) as i32 // Humans have a population between 50-150
-                    } else if gen_at_location[4] == 1 {
-                        (rand::random::<u8>() % 10 + 1) as i32 // Zombies have a population between 1-10
-                    } else {
-                        0 // Empty cells have no population
-                    };
-                    let state = zombie_state::ZombieState::from(gen_at_location);
+#[cfg(feature = "gui")]
+// Seconds a frame may take on average before the quality is lowered
+const FRAME_BUDGET: f64 = 1.0 / 30.0;
 
-                    builder.spawn((
-                        Transform::from_xyz(CELL_SIZE * x as f32, CELL_SIZE * y as f32, 0.),
-                        MooreCell2d::new(IVec2::new(x as i32, y as i32)),
-                        state,
-                    ));
-                }
-            }
-        });
-    println!("Map spawned with size: {}x{}", size_x, size_y);
-}
+/// Seed of the generated terrain.
+#[derive(Resource, Clone, Copy)]
+struct WorldSeed(u64);
 
+/// Generated terrain, `[y][x][layer]` with layer 0 altitude, 1 temperature and 2 moisture (all -1..1).
 #[derive(Resource)]
-struct RectMesh(Handle<Mesh>);
+struct Terrain(Vec<Vec<Vec<f32>>>);
 
-#[derive(Resource)]
-struct TerrainMaterial(Handle<ColorMaterial>);
+/// The cell update and all the rules around it, the same in the window, headless and in the soak
+/// runs. Insert the [`config::SimulationConfig`] before adding them to run with another one.
+struct SimulationPlugins {
+    tick_time_step: Option<f64>, // seconds per tick, none ticks on every update
+}
 
-#[derive(Resource)]
-struct ZombieMaterial(Handle<ColorMaterial>);
+impl PluginGroup for SimulationPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            // Without a time step of its own, the tick sets gate the cell update
+            .add(ZombiePlugin::default())
+            .add(config::SimulationConfigPlugin)
+            .add(tick::TickSetsPlugin {
+                tick_time_step: self.tick_time_step,
+            })
+            .add(daynight::DayNightPlugin)
+            .add(seasons::SeasonsPlugin)
+            .add(weather::WeatherPlugin)
+            .add(cure::CurePlugin)
+            .add(supply::SupplyPlugin)
+            .add(strategy::StrategyPlugin)
+            .add(stats::WorldStatsPlugin)
+            .add(random_events::RandomEventsPlugin)
+    }
+}
 
-#[derive(Resource)]
-struct HumanMaterial(Handle<ColorMaterial>);
+fn main() {
+    if has_arg("--soak") {
+        soak::run();
+        return;
+    }
+    // Broken input files are reported once the window is up, the run goes on without them
+    let mut startup_errors = Vec::new();
+    let config_path = arg_value("--config");
+    let (mut map, rules) = config::load(config_path.as_deref()).unwrap_or_else(|err| {
+        let path = config_path.as_deref().unwrap_or(config::DEFAULT_PATH);
+        startup_errors.push(format!(
+            "Failed to load config {path}, using the defaults: {err}"
+        ));
+        Default::default()
+    });
+    // A save brings its own map along
+    let save = arg_value("--load").and_then(|path| {
+        save::read(&path)
+            .map_err(|err| {
+                startup_errors.push(format!(
+                    "Failed to load save {path}, generating a new map: {err}"
+                ))
+            })
+            .ok()
+    });
+    for err in &startup_errors {
+        eprintln!("{err}");
+    }
+    if let Some(save) = &save {
+        map = save.map.clone();
+    }
+    if let Some(seed) = arg_value("--seed").and_then(|seed| seed.parse().ok()) {
+        map.seed = seed;
+    }
+    if let Some(ticks) = arg_value("--headless").and_then(|ticks| ticks.parse().ok()) {
+        headless::run(
+            ticks,
+            map,
+            rules,
+            save,
+            arg_value("--report").map(Into::into),
+            arg_value("--stats").map(Into::into),
+        );
+        return;
+    }
+    run_game(map, rules, save, startup_errors);
+}
 
-fn setup_assets(
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut commands: Commands,
+/// The game in a window, with all the views and controls.
+#[cfg(feature = "gui")]
+fn run_game(
+    map: MapConfig,
+    rules: config::SimulationConfig,
+    save: Option<save::SaveFile>,
+    startup_errors: Vec<String>,
 ) {
-    let rect = Rectangle::from_size(Vec2::splat(1.0));
-    let rect_mesh_handle = meshes.add(rect);
-
-    let terrain_material_handle = materials.add(Color::from(SANDY_BROWN));
-    let zombie_material_handle = materials.add(Color::from(GREEN));
-    let human_material_handle = materials.add(Color::from(ROYAL_BLUE));
-
-    commands.insert_resource(RectMesh(rect_mesh_handle));
+    let tick_time_step = map.tick_time_step;
+    // Desktop mode only redraws once per tick, or on input
+    let desktop_wake = has_arg("--desktop")
+        .then(|| Duration::from_secs_f64(map.tick_time_step.unwrap_or(DESKTOP_WAKE_UNTIMED)));
+
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            title: "Zombie Test".to_string(),
+            resolution: (1900.0, 1100.0).into(),
+            ..default()
+        }),
+        ..default()
+    }))
+    .insert_resource(rules)
+    .add_plugins(SimulationPlugins {
+        tick_time_step: map.tick_time_step,
+    })
+    .insert_resource(SimulationBatch)
+    .insert_resource(WorldSeed(map.seed))
+    .insert_resource(SimRng::new(map.seed))
+    .insert_resource(map)
+    .add_plugins(loading::LoadingPlugin)
+    .add_systems(Startup, setup_camera)
+    .add_systems(OnEnter(LoadingState::GeneratingTerrain), generate_terrain)
+    .add_systems(OnEnter(LoadingState::SpawningCells), spawn_cells)
+    // Views (tuples of plugins only go up to 15)
+    .add_plugins((
+        cell_views::CellViewsPlugin,
+        terrain_layers::TerrainLayersPlugin,
+        overlays::OverlaysPlugin,
+        contours::ContoursPlugin,
+        chokepoints::ChokepointsPlugin,
+        heatmap::ContestationHeatmapPlugin,
+        lineage::LineagePlugin,
+        corpses::CorpsesPlugin,
+        secured::SecuredPlugin,
+        smell::SmellOverlayPlugin,
+        daynight::NightTintPlugin,
+        weather::WeatherOverlayPlugin,
+        vector_field::VectorFieldPlugin,
+        satellite::SatellitePlugin,
+        photo::PhotoModePlugin,
+    ))
+    .add_plugins(outcome::OutcomePlugin { exit: false });
+
+    app.add_plugins(report::FinalReportPlugin {
+        path: arg_value("--report").map(Into::into),
+    })
+    .add_plugins(chronicle::ChroniclePlugin {
+        path: arg_value("--chronicle").map(Into::into),
+    })
+    .add_plugins(goals::GoalsPlugin)
+    .add_plugins(hud::HudPlugin)
+    .add_plugins(help::HelpPlugin)
+    .add_plugins(toast::ToastPlugin {
+        startup: startup_errors,
+    })
+    .add_plugins(save::SaveLoadPlugin)
+    .add_plugins(checksum::ChecksumPlugin {
+        export: arg_value("--checksums").map(Into::into),
+        verify: arg_value("--checksums-verify").map(Into::into),
+    })
+    .add_plugins(idle::IdlePlugin {
+        behavior: match arg_value("--idle").as_deref() {
+            Some("run") => idle::IdleBehavior::Run,
+            Some("pause") => idle::IdleBehavior::Pause,
+            _ => idle::IdleBehavior::Slow,
+        },
+        focused_wake: desktop_wake,
+    })
+    .add_plugins(quality::QualityPlugin {
+        start: match arg_value("--quality").as_deref() {
+            Some("low") => quality::Quality::Low,
+            Some("medium") => quality::Quality::Medium,
+            _ => quality::Quality::High,
+        },
+        auto: !has_arg("--fixed-quality"),
+        frame_budget: Duration::from_secs_f64(FRAME_BUDGET),
+    });
+    if let Some(save) = save {
+        app.insert_resource(save);
+    }
+    app.add_plugins(speed::SpeedControlsPlugin { tick_time_step });
+    if let Some(path) = arg_value("--stats") {
+        app.add_plugins(stats_export::StatsExportPlugin { path: path.into() });
+    }
+    if has_arg("--profile-stages") {
+        app.add_plugins(profiler::StageProfilerPlugin);
+    }
+    add_dev_tools(&mut app);
+    add_exporters(&mut app);
 
-    commands.insert_resource(TerrainMaterial(terrain_material_handle));
-    commands.insert_resource(ZombieMaterial(zombie_material_handle));
-    commands.insert_resource(HumanMaterial(human_material_handle));
+    app.run();
 }
 
-#[derive(Component, Clone, Copy)]
-struct Humans;
-
-#[derive(Component, Clone, Copy)]
-struct Zombies;
-
-fn setup_views(
-    cells_q: Query<Entity, With<ZombieState>>,
-    mut commands: Commands,
-    rect_mesh: Res<RectMesh>,
-    terrain_material: Res<TerrainMaterial>,
-    zombie_material: Res<ZombieMaterial>,
-    human_material: Res<HumanMaterial>,
+#[cfg(not(feature = "gui"))]
+fn run_game(
+    _map: MapConfig,
+    _rules: config::SimulationConfig,
+    _save: Option<save::SaveFile>,
+    _startup_errors: Vec<String>,
 ) {
-    let terrain = (
-        Mesh2d(rect_mesh.0.clone()),
-        MeshMaterial2d(terrain_material.0.clone()),
-        Transform {
-            translation: Vec3::new(0.0, 0.0, 1.0),
-            scale: Vec3::new(CELL_SIZE, CELL_SIZE, 1.0),
-            ..default()
-        },
+    eprintln!(
+        "Built without the gui feature, only --headless <ticks> and --soak runs are available"
     );
+}
 
-    let humans = (
-        Mesh2d(rect_mesh.0.clone()),
-        MeshMaterial2d(human_material.0.clone()),
-        Transform {
-            translation: Vec3::new(0.0, 0.0, 3.0), // atop terrain
-            scale: Vec3::new(0.0, 0.0, 1.0),
-            ..default()
-        },
-        Humans,
-    );
+/// The inspector and the paint brush, built with the `dev_tools` feature.
+#[cfg(feature = "dev_tools")]
+fn add_dev_tools(app: &mut App) {
+    app.add_plugins((inspector::InspectorPlugin, paint::PaintPlugin));
+}
 
-    let zombies = (
-        Mesh2d(rect_mesh.0.clone()),
-        MeshMaterial2d(zombie_material.0.clone()),
-        Transform {
-            translation: Vec3::new(0.0, 0.0, 2.0), // atop humans
-            scale: Vec3::new(0.0, 0.0, 1.0),
-            ..default()
-        },
-        Zombies,
-    );
+#[cfg(all(feature = "gui", not(feature = "dev_tools")))]
+fn add_dev_tools(_app: &mut App) {}
 
-    for cell in cells_q.iter() {
-        commands
-            .entity(cell)
-            .with_child(terrain.clone())
-            .with_child(humans.clone())
-            .with_child(zombies.clone());
+/// `--flows`, `--video` and `--telemetry`, built with the `exporters` feature.
+#[cfg(feature = "exporters")]
+fn add_exporters(app: &mut App) {
+    if let Some(path) = arg_value("--flows") {
+        app.add_plugins(flows::FlowExportPlugin {
+            path: path.into(),
+            region_size: arg_value("--flows-region").and_then(|size| size.parse().ok()),
+        });
+    }
+    if let Some(dir) = arg_value("--video") {
+        app.add_plugins(video::VideoPlugin {
+            dir: dir.into(),
+            fps: 60,
+        });
+    }
+    if let Some(path) = arg_value("--telemetry") {
+        app.add_plugins(telemetry::TelemetryPlugin { path: path.into() });
     }
 }
 
-const CELL_MAX_POPULATION: i32 = 1000;
-const CELL_MAX_HALF_POPULATION: i32 = CELL_MAX_POPULATION / 2;
+#[cfg(all(feature = "gui", not(feature = "exporters")))]
+fn add_exporters(_app: &mut App) {
+    for flag in ["--flows", "--video", "--telemetry"] {
+        if has_arg(flag) {
+            warn!("{flag} ignored, built without the exporters feature");
+        }
+    }
+}
 
-fn update_cell_views(
-    cells_q: Query<(&ZombieState, &Children)>,
-    mut humans_tfs_q: Query<&mut Transform, (With<Humans>, Without<Zombies>)>,
-    mut zombies_tfs_q: Query<&mut Transform, (With<Zombies>, Without<Humans>)>,
-) {
-    for (state, children) in cells_q.iter() {
-        let ch = children.to_vec();
-        let humans_e = ch[1];
-        let zombies_e = ch[2];
+/// Value following `name` on the command line, e.g. `--telemetry run.csv`.
+fn arg_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != name);
+    args.next()?;
+    args.next()
+}
 
-        let mut humans_tf = humans_tfs_q.get_mut(humans_e).unwrap();
-        let mut zombies_tf = zombies_tfs_q.get_mut(zombies_e).unwrap();
+fn has_arg(name: &str) -> bool {
+    std::env::args().any(|arg| arg == name)
+}
 
-        let population_scale =
-            (state.population as f32 / CELL_MAX_POPULATION as f32).min(1.0) * CELL_HALF_SIZE / 2.0;
+#[cfg(feature = "gui")]
+fn setup_camera(mut commands: Commands) {
+    commands.spawn(Camera2d);
+}
 
-        let scale = Vec3::new(population_scale, population_scale, 1.0);
+const CELL_SIZE: f32 = 12.0;
 
-        match state.status {
-            Status::Empty => {
-                humans_tf.scale = Vec3::ZERO;
-                zombies_tf.scale = Vec3::ZERO;
-            }
-            Status::Zombie => {
-                humans_tf.scale = Vec3::ZERO;
-                zombies_tf.scale = (scale * Vec3::new(25.0, 25.0, 1.0)).min(Vec3::new(
-                    CELL_HALF_SIZE,
-                    CELL_HALF_SIZE,
-                    1.0,
-                ));
-            }
-            Status::Human => {
-                humans_tf.scale = scale;
-                zombies_tf.scale = Vec3::ZERO;
-            }
-        }
-    }
+fn generate_terrain(
+    mut commands: Commands,
+    seed: Res<WorldSeed>,
+    map: Res<MapConfig>,
+    save: Option<Res<save::SaveFile>>,
+) {
+    let terrain = match &save {
+        Some(save) => save.terrain.clone(),
+        None => terrain::TerrainGenerator::new(seed.0).generate(
+            map.width,
+            map.height,
+            map.octaves,
+            map.terrain_scale,
+        ),
+    };
+    commands.insert_resource(Terrain(terrain));
 }
 
-fn state_debug(
-    cells_q: Query<(&ZombieState, &Children)>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+fn spawn_cells(
     mut commands: Commands,
+    mut rng: ResMut<SimRng>,
+    map: Res<MapConfig>,
+    terrain: Res<Terrain>,
+    seed: Res<WorldSeed>,
+    rules: Res<config::SimulationConfig>,
+    save: Option<Res<save::SaveFile>>,
 ) {
-    for (state, children) in cells_q.iter() {
-        let terrain_e = children.get(0).unwrap();
-
-        commands
-            .entity(*terrain_e)
-            .insert(MeshMaterial2d(materials.add(Color::srgba(
-                1.0,
-                0.0,
-                0.0,
-                state.smell_zombie as f32 / 1000.0,
-            ))));
+    let rules = config::Rules::new(*rules);
+    let (size_x, size_y) = (map.width, map.height);
+    let terrain = &terrain.0;
+    let mut features = terrain::TerrainGenerator::new(seed.0).features(terrain, map.water_level);
+    for (y, row) in features.kinds.iter_mut().enumerate() {
+        for (x, kind) in row.iter_mut().enumerate() {
+            let xy = IVec2::new(x as i32, y as i32);
+            if map.barriers.iter().any(|barrier| barrier.contains(xy)) {
+                *kind = terrain::TerrainKind::Wall;
+            }
+        }
     }
+    let settlers = settlements::seed_settlements(&map.settlements, &features, &mut rng.0);
+    let kinds = &features.kinds;
+    let chokepoints = terrain::find_chokepoints(kinds);
+
+    commands
+        .spawn((Transform::from_xyz(
+            -(size_x as f32 * CELL_SIZE) / 2.,
+            -(size_y as f32 * CELL_SIZE) / 2.,
+            0.,
+        ),))
+        .with_children(|builder| {
+            for y in 0..size_y {
+                for x in 0..size_x {
+                    // Resumed cells as they were saved, or new ones
+                    let state = match &save {
+                        Some(save) => ZombieState {
+                            rules: rules.clone(),
+                            ..save.cells[y * size_x + x].clone()
+                        },
+                        None => {
+                            let xy = IVec2::new(x as i32, y as i32);
+                            let [altitude, temperature, moisture] =
+                                [0, 1, 2].map(|layer| terrain[y][x][layer]);
+                            let ground = ZombieState::builder(xy)
+                                .altitude((altitude * SCALE as f32) as i32)
+                                .temperature((temperature * SCALE as f32) as i32)
+                                .moisture((moisture * SCALE as f32) as i32)
+                                .terrain(kinds[y][x])
+                                .biome(terrain::Biome::at(altitude, temperature, moisture))
+                                .chokepoint(chokepoints[y][x])
+                                .open_edge(
+                                    map.boundary == boundary::Boundary::Open
+                                        && boundary::GridCell::on_edge(xy, &map),
+                                )
+                                .food(rules.food_capacity) // Stores start full
+                                .rules(rules.clone());
+
+                            // Cities and outbreaks from the settlement generator, everywhere else is empty
+                            let settler = &settlers[y][x];
+                            ground
+                                .clone()
+                                .status(settler.status.clone())
+                                .population(settler.population)
+                                .lineage(settler.lineage)
+                                .faction(settler.faction)
+                                .variants(if settler.status.is_zombie() {
+                                    map.settlements.outbreak_variants
+                                } else {
+                                    [0; 3]
+                                })
+                                .build()
+                                .unwrap_or_else(|err| {
+                                    warn!("{err}, the cell is left empty");
+                                    ground.build().expect("empty ground is a valid cell")
+                                })
+                        }
+                    };
+
+                    builder.spawn((
+                        Transform::from_xyz(CELL_SIZE * x as f32, CELL_SIZE * y as f32, 0.),
+                        boundary::GridCell::new(IVec2::new(x as i32, y as i32), &map),
+                        state,
+                    ));
+                }
+            }
+        });
+    commands.remove_resource::<save::SaveFile>();
+    println!("Map spawned with size: {}x{}", size_x, size_y);
 }
@@ -0,0 +1,262 @@
+use crate::chronicle::{ChronicleSet, Headline};
+use crate::config::MapConfig;
+use crate::stats::{TickStats, WorldStats, sample_world_stats};
+use crate::tick::{Bookkeeping, TickClock};
+#[cfg(feature = "gui")]
+use crate::{overlays::map_image, photo::PhotoMode};
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+const GRAPH_SIZE: (usize, usize) = (60, 10); // characters of the territory graph in the log
+#[cfg(feature = "gui")]
+const CHART_SIZE: (usize, usize) = (300, 80); // pixels of the one on screen
+
+/// Ends the run once either side is extinct, or after [`MapConfig::stalemate_ticks`] of stalemate:
+/// the automaton stops, and a summary of the run is logged and shown in the middle of the screen.
+/// With `exit` (headless runs) the app exits instead, which writes the final report.
+pub struct OutcomePlugin {
+    pub exit: bool,
+}
+
+impl Plugin for OutcomePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            detect_outcome
+                .in_set(Bookkeeping)
+                .after(sample_world_stats)
+                .before(ChronicleSet),
+        );
+        if self.exit {
+            app.add_systems(Update, exit_on_outcome.run_if(resource_added::<Outcome>));
+        } else {
+            #[cfg(feature = "gui")]
+            app.add_systems(
+                Update,
+                (
+                    show_summary.run_if(resource_added::<Outcome>),
+                    hide_summary_in_photos,
+                ),
+            );
+        }
+    }
+}
+
+/// How the run ended, inserted on the tick it did.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    HumansWon,  // the zombies are extinct
+    ZombiesWon, // the humans are
+    MutualExtinction,
+    Stalemate, // no cell changed hands and nobody turned for `stalemate_ticks`
+}
+
+impl Outcome {
+    fn describe(&self) -> &'static str {
+        match self {
+            Outcome::HumansWon => "the humans won, the zombies were eradicated",
+            Outcome::ZombiesWon => "the zombies won, humanity is extinct",
+            Outcome::MutualExtinction => "nobody is left, humans or zombies",
+            Outcome::Stalemate => "stalemate, the front hasn't moved in a long time",
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+#[derive(Component)]
+struct SummaryPanel;
+
+fn detect_outcome(
+    map: Res<MapConfig>,
+    stats: Res<WorldStats>,
+    mut seen: Local<(bool, bool)>, // humans, zombies on the map at some point
+    mut clock: ResMut<TickClock>,
+    mut headlines: EventWriter<Headline>,
+    mut commands: Commands,
+) {
+    let Some(latest) = stats.latest() else {
+        return;
+    };
+    seen.0 |= latest.humans > 0;
+    seen.1 |= latest.zombies > 0;
+
+    // Only a side that was there can die out, a map without outbreaks is a sandbox
+    let humans_gone = seen.0 && latest.humans == 0;
+    let zombies_gone = seen.1 && latest.zombies == 0;
+    let outcome = if humans_gone && latest.zombies == 0 {
+        Outcome::MutualExtinction
+    } else if humans_gone {
+        Outcome::ZombiesWon
+    } else if zombies_gone {
+        Outcome::HumansWon
+    } else if is_stalemate(&stats, map.stalemate_ticks) {
+        Outcome::Stalemate
+    } else {
+        return;
+    };
+
+    headlines.send(Headline(outcome.describe().to_string()));
+    info!(
+        "{}land held by humans (H), zombies (Z) and neither (.) over the run:\n{}",
+        summary(Some(outcome), stats.history()),
+        territory_graph(stats.history())
+    );
+    commands.insert_resource(outcome);
+    clock.stop();
+}
+
+/// No cell changed hands between humans and zombies and nobody turned over the last `ticks` ticks.
+fn is_stalemate(stats: &WorldStats, ticks: u64) -> bool {
+    let recent = stats.recent(ticks as usize);
+    ticks > 0
+        && recent.len() == ticks as usize
+        && recent
+            .iter()
+            .all(|sample| sample.cells_captured == 0 && sample.conversions == 0)
+}
+
+/// How the run went, up to how it ended if it did.
+pub fn summary(outcome: Option<Outcome>, history: &[TickStats]) -> String {
+    let last = history.last().copied().unwrap_or_default();
+    // The first tick it was reached
+    let peak = |population: fn(&TickStats) -> i64| {
+        history
+            .iter()
+            .rev()
+            .max_by_key(|sample| population(sample))
+            .map_or((0, 0), |sample| (population(sample), sample.tick))
+    };
+    let (peak_humans, peak_zombies) = (peak(|sample| sample.humans), peak(|sample| sample.zombies));
+    let ending = match outcome {
+        Some(outcome) => format!("Run over: {}", outcome.describe()),
+        None => "Run stopped before either side won".to_string(),
+    };
+    format!(
+        "{ending}\n\
+         ticks survived: {}\n\
+         final populations: {} humans, {} zombies\n\
+         peak humans: {} at tick {}\n\
+         peak zombies: {} at tick {}\n",
+        last.tick,
+        last.humans,
+        last.zombies,
+        peak_humans.0,
+        peak_humans.1,
+        peak_zombies.0,
+        peak_zombies.1,
+    )
+}
+
+enum Holder {
+    Humans,
+    Zombies,
+    Neither,
+}
+
+/// Who held the land `level` percent up column `x` of a chart `width` wide squeezing in the whole
+/// run, humans at the bottom as in the HUD.
+fn holder_at(history: &[TickStats], x: usize, width: usize, level: f32) -> Holder {
+    let Some(sample) = history.get(x * history.len() / width.max(1)) else {
+        return Holder::Neither;
+    };
+    let (humans, zombies, _) = sample.control_percent();
+    if level < humans {
+        Holder::Humans
+    } else if level < humans + zombies {
+        Holder::Zombies
+    } else {
+        Holder::Neither
+    }
+}
+
+fn territory_graph(history: &[TickStats]) -> String {
+    let (width, height) = (GRAPH_SIZE.0.min(history.len()), GRAPH_SIZE.1);
+    let mut graph = String::new();
+    for y in (0..height).rev() {
+        let level = (y as f32 + 0.5) * 100.0 / height as f32;
+        graph.extend(
+            (0..width).map(|x| match holder_at(history, x, width, level) {
+                Holder::Humans => 'H',
+                Holder::Zombies => 'Z',
+                Holder::Neither => '.',
+            }),
+        );
+        graph.push('\n');
+    }
+    graph
+}
+
+fn exit_on_outcome(mut exit: EventWriter<AppExit>) {
+    exit.send(AppExit::Success);
+}
+
+#[cfg(feature = "gui")]
+fn show_summary(
+    outcome: Res<Outcome>,
+    stats: Res<WorldStats>,
+    mut images: ResMut<Assets<Image>>,
+    mut commands: Commands,
+) {
+    let history = stats.history();
+    let (width, height) = CHART_SIZE;
+    let chart = images.add(map_image(width, height, |x, y| {
+        let level = (y as f32 + 0.5) * 100.0 / height as f32;
+        match holder_at(history, x, width, level) {
+            Holder::Humans => [65, 105, 225, 255], // royal blue, as the human cells
+            Holder::Zombies => [0, 128, 0, 255],   // green, as the zombie cells
+            Holder::Neither => [110, 110, 110, 255],
+        }
+    }));
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            SummaryPanel,
+        ))
+        .with_children(|screen| {
+            screen
+                .spawn((
+                    Node {
+                        padding: UiRect::all(Val::Px(12.0)),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(8.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+                ))
+                .with_children(|panel| {
+                    panel.spawn((
+                        Text(summary(Some(*outcome), history)),
+                        TextFont::from_font_size(20.0),
+                    ));
+                    panel.spawn((
+                        ImageNode::new(chart),
+                        Node {
+                            width: Val::Px(width as f32),
+                            height: Val::Px(height as f32),
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+#[cfg(feature = "gui")]
+fn hide_summary_in_photos(
+    photo: Option<Res<PhotoMode>>,
+    mut panel_q: Query<&mut Visibility, With<SummaryPanel>>,
+) {
+    for mut visibility in panel_q.iter_mut() {
+        visibility.set_if_neq(match photo {
+            Some(_) => Visibility::Hidden,
+            None => Visibility::Inherited,
+        });
+    }
+}
@@ -0,0 +1,121 @@
+use crate::CELL_SIZE;
+use crate::config::MapConfig;
+use crate::help::KeybindingsAppExt;
+use crate::photo::PhotoMode;
+use bevy::image::ImageSampler;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+/// Which optional overlays are drawn atop the map, each one toggled by its own key.
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct Overlays {
+    pub contours: bool,     // C
+    pub chokepoints: bool,  // K
+    pub contestation: bool, // B
+    pub lineage: bool,      // O
+    pub vector_field: bool, // V
+    pub corpses: bool,      // X
+    pub smell: bool,        // M
+}
+
+pub struct OverlaysPlugin;
+
+impl Plugin for OverlaysPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_keybindings(
+            "Overlays",
+            &[
+                ("C", "contour lines"),
+                ("K", "chokepoints"),
+                ("B", "contestation heatmap"),
+                ("O", "lineage"),
+                ("V", "movement vector field"),
+                ("X", "corpses"),
+                ("M", "zombie smell (high quality only)"),
+            ],
+        )
+        .init_resource::<Overlays>()
+        .add_systems(
+            Update,
+            toggle_overlays.run_if(not(resource_exists::<PhotoMode>)),
+        );
+    }
+}
+
+fn toggle_overlays(keys: Res<ButtonInput<KeyCode>>, mut overlays: ResMut<Overlays>) {
+    // Shift + overlay key is left for the overlay's own action, e.g. export
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        return;
+    }
+    for key in keys.get_just_pressed() {
+        let (name, enabled) = match key {
+            KeyCode::KeyC => ("contours", &mut overlays.contours),
+            KeyCode::KeyK => ("chokepoints", &mut overlays.chokepoints),
+            KeyCode::KeyB => ("contestation heatmap", &mut overlays.contestation),
+            KeyCode::KeyO => ("lineage", &mut overlays.lineage),
+            KeyCode::KeyV => ("movement vector field", &mut overlays.vector_field),
+            KeyCode::KeyX => ("corpses", &mut overlays.corpses),
+            KeyCode::KeyM => ("zombie smell", &mut overlays.smell),
+            _ => continue,
+        };
+        *enabled = !*enabled;
+        info!("Overlay {name}: {}", if *enabled { "on" } else { "off" });
+    }
+}
+
+/// One-texel-per-cell RGBA image of the map, `pixel` gets map coordinates (y up).
+pub fn map_image(width: usize, height: usize, pixel: impl Fn(usize, usize) -> [u8; 4]) -> Image {
+    let mut data = Vec::with_capacity(width * height * 4);
+    // Image rows go top to bottom
+    for y in (0..height).rev() {
+        for x in 0..width {
+            data.extend_from_slice(&pixel(x, y));
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::nearest();
+    image
+}
+
+/// Sprite stretching a map image over the whole map at height `z`.
+pub fn map_sprite(map: &MapConfig, image: Handle<Image>, z: f32) -> (Sprite, Transform) {
+    (
+        Sprite {
+            image,
+            custom_size: Some(Vec2::new(
+                map.width as f32 * CELL_SIZE,
+                map.height as f32 * CELL_SIZE,
+            )),
+            ..default()
+        },
+        // Cells are anchored at their centers
+        Transform::from_xyz(-CELL_SIZE / 2.0, -CELL_SIZE / 2.0, z),
+    )
+}
+
+/// World position of the center of the cell at `xy`, fractional coordinates lie between cells.
+pub fn cell_to_world(map: &MapConfig, xy: Vec2) -> Vec2 {
+    let origin = -Vec2::new(map.width as f32, map.height as f32) * CELL_SIZE / 2.0;
+    origin + xy * CELL_SIZE
+}
+
+/// Cell under the world position, if it's on the map.
+#[cfg(feature = "dev_tools")]
+pub fn world_to_cell(map: &MapConfig, world: Vec2) -> Option<IVec2> {
+    let origin = -Vec2::new(map.width as f32, map.height as f32) * CELL_SIZE / 2.0;
+    let xy = ((world - origin) / CELL_SIZE).round().as_ivec2();
+    (xy.x >= 0 && xy.y >= 0 && (xy.x as usize) < map.width && (xy.y as usize) < map.height)
+        .then_some(xy)
+}
@@ -0,0 +1,185 @@
+use crate::config::MapConfig;
+use crate::help::KeybindingsAppExt;
+use crate::inspector::cell_under_cursor;
+use crate::photo::PhotoMode;
+use crate::speed::SimulationSpeed;
+use crate::zombie_state::{MAX_INCUBATION_TICKS, Status, ZombieState};
+use bevy::prelude::*;
+
+const POPULATION_RANGE: (i32, i32) = (1, 1000);
+
+/// Paint mode (E) for setting up scenarios: while the simulation is paused, dragging the
+/// mouse paints humans (1), zombies (2) or clears cells (3). `[` and `]` halve or double
+/// the population painted.
+pub struct PaintPlugin;
+
+impl Plugin for PaintPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_keybindings(
+            "Paint mode",
+            &[
+                ("E", "enter or leave paint mode"),
+                ("1 / 2 / 3", "paint humans, zombies or clear"),
+                ("[ / ]", "halve or double the population painted"),
+                ("Left drag", "paint while paused"),
+            ],
+        )
+        .add_systems(Startup, setup_paint_indicator)
+        .add_systems(
+            Update,
+            (
+                toggle_paint_mode,
+                (pick_brush, paint_cells).run_if(resource_exists::<PaintMode>),
+                update_paint_indicator,
+            )
+                .chain()
+                .run_if(not(resource_exists::<PhotoMode>)),
+        );
+    }
+}
+
+/// Present while in paint mode.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct PaintMode {
+    pub brush: Brush,
+    pub population: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Brush {
+    Humans,
+    Zombies,
+    Clear,
+}
+
+#[derive(Component)]
+struct PaintIndicator;
+
+fn toggle_paint_mode(
+    keys: Res<ButtonInput<KeyCode>>,
+    paint: Option<Res<PaintMode>>,
+    mut commands: Commands,
+) {
+    if !keys.just_pressed(KeyCode::KeyE) {
+        return;
+    }
+    match paint {
+        None => {
+            commands.insert_resource(PaintMode {
+                brush: Brush::Humans,
+                population: 100,
+            });
+            info!("Paint mode on, pause the simulation (Space) to paint");
+        }
+        Some(_) => {
+            commands.remove_resource::<PaintMode>();
+            info!("Paint mode off");
+        }
+    }
+}
+
+fn pick_brush(keys: Res<ButtonInput<KeyCode>>, mut paint: ResMut<PaintMode>) {
+    for key in keys.get_just_pressed() {
+        match key {
+            KeyCode::Digit1 => paint.brush = Brush::Humans,
+            KeyCode::Digit2 => paint.brush = Brush::Zombies,
+            KeyCode::Digit3 => paint.brush = Brush::Clear,
+            KeyCode::BracketLeft => {
+                paint.population = (paint.population / 2).max(POPULATION_RANGE.0)
+            }
+            KeyCode::BracketRight => {
+                paint.population = (paint.population * 2).min(POPULATION_RANGE.1)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn paint_cells(
+    mouse: Res<ButtonInput<MouseButton>>,
+    paint: Res<PaintMode>,
+    speed: Option<Res<SimulationSpeed>>,
+    map: Res<MapConfig>,
+    windows_q: Query<&Window>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    mut cells_q: Query<&mut ZombieState>,
+) {
+    // Painting mid-tick would be overwritten by the cell update
+    if !mouse.pressed(MouseButton::Left) || !speed.is_some_and(|speed| speed.paused) {
+        return;
+    }
+    let Some(xy) = cell_under_cursor(&map, &windows_q, &camera_q) else {
+        return;
+    };
+    let Some(mut state) = cells_q.iter_mut().find(|state| state.xy == xy) else {
+        return;
+    };
+    // Nobody can stand on water or rivers
+    if state.terrain.is_impassable() {
+        return;
+    }
+
+    let (status, population) = match paint.brush {
+        Brush::Humans => (Status::Human, paint.population),
+        Brush::Zombies => (Status::Zombie, paint.population),
+        Brush::Clear => (Status::Empty, 0),
+    };
+    if state.status == status && state.population == population {
+        return;
+    }
+    state.status = status;
+    state.population = population;
+    state.stay();
+    // A painted group is its own outbreak or settlement, like the ones spawned at the start
+    state.lineage = if population > 0 {
+        (xy.y as usize * map.width + xy.x as usize + 1) as u32
+    } else {
+        0
+    };
+    // Painted humans join the first faction
+    state.faction = state.status.is_human() as u8;
+    if !state.status.is_zombie() {
+        state.variants = [0; 3];
+    }
+    state.incubating = [0; MAX_INCUBATION_TICKS];
+    state.camp = false;
+    state.hiding = false;
+    state.encircled = 0;
+    state.panic = 0;
+    state.panic_source = 8;
+}
+
+fn setup_paint_indicator(mut commands: Commands) {
+    commands.spawn((
+        Text::default(),
+        TextFont::from_font_size(16.0),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            right: Val::Px(8.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        PaintIndicator,
+    ));
+}
+
+fn update_paint_indicator(
+    paint: Option<Res<PaintMode>>,
+    speed: Option<Res<SimulationSpeed>>,
+    mut indicator_q: Query<(&mut Text, &mut Visibility), With<PaintIndicator>>,
+) {
+    for (mut text, mut visibility) in indicator_q.iter_mut() {
+        let Some(paint) = &paint else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+        let paused = speed.as_ref().is_some_and(|speed| speed.paused);
+        text.0 = match (paint.brush, paused) {
+            (_, false) => "Paint: pause to paint".to_string(),
+            (Brush::Clear, true) => "Paint: clear".to_string(),
+            (brush, true) => format!("Paint: {brush:?} x{}", paint.population),
+        };
+    }
+}
@@ -0,0 +1,120 @@
+use crate::help::KeybindingsAppExt;
+use crate::overlays::Overlays;
+use bevy::input::mouse::AccumulatedMouseScroll;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, save_to_disk};
+
+const PAN_SPEED: f32 = 600.0; // world units per second at zoom 1
+const ZOOM_STEP: f32 = 0.1; // share of the zoom changed per scroll line
+const ZOOM_RANGE: (f32, f32) = (0.05, 10.0);
+
+/// Photo mode (P): pauses the simulation, hides the overlays and frees the camera
+/// (WASD or arrows to pan, scroll to zoom). Enter saves a screenshot, P again restores everything.
+pub struct PhotoModePlugin;
+
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_keybindings(
+            "Photo mode",
+            &[
+                ("P", "enter or leave photo mode"),
+                ("WASD/arrows", "pan the camera"),
+                ("Scroll", "zoom"),
+                ("Enter", "save a screenshot"),
+            ],
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_photo_mode,
+                (move_photo_camera, take_photo).run_if(resource_exists::<PhotoMode>),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Present while in photo mode, holding what to restore when leaving it.
+#[derive(Resource)]
+pub struct PhotoMode {
+    overlays: Overlays,
+    camera: (Transform, f32), // transform and projection scale
+}
+
+fn toggle_photo_mode(
+    keys: Res<ButtonInput<KeyCode>>,
+    photo: Option<Res<PhotoMode>>,
+    mut overlays: ResMut<Overlays>,
+    mut time: ResMut<Time<Virtual>>,
+    mut camera_q: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+    mut commands: Commands,
+) {
+    if !keys.just_pressed(KeyCode::KeyP) {
+        return;
+    }
+    let Ok((mut transform, mut projection)) = camera_q.get_single_mut() else {
+        return;
+    };
+
+    match photo {
+        None => {
+            commands.insert_resource(PhotoMode {
+                overlays: std::mem::take(&mut *overlays),
+                camera: (*transform, projection.scale),
+            });
+            time.pause();
+            info!("Photo mode on");
+        }
+        Some(photo) => {
+            *overlays = photo.overlays;
+            (*transform, projection.scale) = photo.camera;
+            commands.remove_resource::<PhotoMode>();
+            time.unpause();
+            info!("Photo mode off");
+        }
+    }
+}
+
+fn move_photo_camera(
+    keys: Res<ButtonInput<KeyCode>>,
+    scroll: Res<AccumulatedMouseScroll>,
+    time: Res<Time<Real>>, // virtual time is paused
+    mut camera_q: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+) {
+    let Ok((mut transform, mut projection)) = camera_q.get_single_mut() else {
+        return;
+    };
+
+    let mut direction = Vec2::ZERO;
+    for (keys_for, step) in [
+        ([KeyCode::KeyW, KeyCode::ArrowUp], Vec2::Y),
+        ([KeyCode::KeyS, KeyCode::ArrowDown], Vec2::NEG_Y),
+        ([KeyCode::KeyA, KeyCode::ArrowLeft], Vec2::NEG_X),
+        ([KeyCode::KeyD, KeyCode::ArrowRight], Vec2::X),
+    ] {
+        if keys.any_pressed(keys_for) {
+            direction += step;
+        }
+    }
+    let pan = direction.normalize_or_zero() * PAN_SPEED * projection.scale * time.delta_secs();
+    transform.translation += pan.extend(0.0);
+
+    if scroll.delta.y != 0.0 {
+        projection.scale = (projection.scale * (1.0 - scroll.delta.y * ZOOM_STEP))
+            .clamp(ZOOM_RANGE.0, ZOOM_RANGE.1);
+    }
+}
+
+fn take_photo(keys: Res<ButtonInput<KeyCode>>, mut commands: Commands) {
+    if !keys.just_pressed(KeyCode::Enter) {
+        return;
+    }
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let path = format!("photo-{seconds}.png");
+    info!("Saving photo to {path}");
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path));
+}
@@ -0,0 +1,62 @@
+use crate::tick::{Bookkeeping, TickSet, tick_due};
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy::prelude::*;
+use bevy_life::LifeSystemSet;
+use std::time::Instant;
+
+/// The cell update, our bookkeeping and the hooks of other plugins, in ms per tick.
+pub const STAGES: [DiagnosticPath; 3] = [
+    DiagnosticPath::const_new("tick/cell_update"), // fighting, smell and deciding, all in one step
+    DiagnosticPath::const_new("tick/bookkeeping"),
+    DiagnosticPath::const_new("tick/hooks"), // the TickSet systems
+];
+
+/// Opt-in (`--profile-stages`) timing of each stage of every tick into the diagnostics, so it's
+/// clear which one to speed up on big maps. Marks are taken between the stages, systems outside
+/// them running alongside are counted in whichever stage they overlap.
+pub struct StageProfilerPlugin;
+
+impl Plugin for StageProfilerPlugin {
+    fn build(&self, app: &mut App) {
+        for path in STAGES {
+            app.register_diagnostic(Diagnostic::new(path).with_suffix("ms"));
+        }
+        app.init_resource::<StageMarks>().add_systems(
+            Update,
+            (
+                mark::<0>.before(TickSet::PreCombat),
+                mark::<1>
+                    .after(TickSet::PreCombat)
+                    .before(LifeSystemSet::CellUpdate),
+                mark::<2>
+                    .after(LifeSystemSet::CellUpdate)
+                    .before(TickSet::PostCombat),
+                mark::<3>.after(TickSet::PostCombat).before(Bookkeeping),
+                mark::<4>.after(Bookkeeping).before(TickSet::PostDecision),
+                (mark::<5>, record_stages)
+                    .chain()
+                    .after(TickSet::PostDecision),
+            )
+                .run_if(tick_due),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+struct StageMarks([Option<Instant>; 6]);
+
+fn mark<const AT: usize>(mut marks: ResMut<StageMarks>) {
+    marks.0[AT] = Some(Instant::now());
+}
+
+fn record_stages(mut marks: ResMut<StageMarks>, mut diagnostics: Diagnostics) {
+    let Some(at) = marks.0.into_iter().collect::<Option<Vec<Instant>>>() else {
+        return;
+    };
+    let ms = |from: usize, to: usize| (at[to] - at[from]).as_secs_f64() * 1000.0;
+    let [cell_update, bookkeeping, hooks] = &STAGES;
+    diagnostics.add_measurement(cell_update, || ms(1, 2));
+    diagnostics.add_measurement(bookkeeping, || ms(3, 4));
+    diagnostics.add_measurement(hooks, || ms(0, 1) + ms(2, 3) + ms(4, 5));
+    marks.0 = Default::default();
+}
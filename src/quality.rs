@@ -0,0 +1,141 @@
+use crate::help::KeybindingsAppExt;
+use crate::photo::PhotoMode;
+use bevy::prelude::*;
+use bevy::winit::{UpdateMode, WinitSettings};
+use std::time::Duration;
+
+const SAMPLE_FRAMES: u32 = 120; // frames averaged before deciding to step down
+const LOW_VIEW_EVERY: u32 = 4; // frames between cell view updates at Quality::Low
+
+/// Quality ladder for big maps on weak hardware, shown in the top right corner.
+/// Starts at `start` and, with `auto`, steps down a level whenever frames take longer than
+/// `frame_budget` on average. Q steps back up by hand.
+pub struct QualityPlugin {
+    pub start: Quality,
+    pub auto: bool,
+    pub frame_budget: Duration,
+}
+
+impl Plugin for QualityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_keybindings("Quality", &[("Q", "raise the quality a level")])
+            .insert_resource(self.start)
+            .insert_resource(FrameBudget(self.frame_budget))
+            .add_systems(Startup, setup_quality_indicator)
+            .add_systems(Update, (raise_quality, update_quality_indicator).chain());
+        if self.auto {
+            app.add_systems(Update, step_down_quality.before(raise_quality));
+        }
+    }
+}
+
+/// What gets drawn, each level drops some more of the per-frame work.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Quality {
+    Low,    // cell views only refreshed every LOW_VIEW_EVERY frames
+    Medium, // no smell overlay
+    #[default]
+    High,
+}
+
+impl Quality {
+    fn lower(self) -> Option<Quality> {
+        match self {
+            Quality::High => Some(Quality::Medium),
+            Quality::Medium => Some(Quality::Low),
+            Quality::Low => None,
+        }
+    }
+
+    fn higher(self) -> Option<Quality> {
+        match self {
+            Quality::Low => Some(Quality::Medium),
+            Quality::Medium => Some(Quality::High),
+            Quality::High => None,
+        }
+    }
+}
+
+/// Run condition for refreshing the cell views.
+pub fn cell_views_due(quality: Res<Quality>, mut frame: Local<u32>) -> bool {
+    *frame = frame.wrapping_add(1);
+    *quality > Quality::Low || frame.is_multiple_of(LOW_VIEW_EVERY)
+}
+
+#[derive(Resource)]
+struct FrameBudget(Duration);
+
+#[derive(Component)]
+struct QualityIndicator;
+
+fn step_down_quality(
+    time: Res<Time<Real>>,
+    budget: Res<FrameBudget>,
+    winit: Res<WinitSettings>,
+    windows_q: Query<&Window>,
+    mut quality: ResMut<Quality>,
+    mut samples: Local<(u32, Duration)>, // frames, their total time
+) {
+    // Frame times only say something while redrawing as fast as possible
+    let focused = windows_q.iter().any(|window| window.focused);
+    if !focused || !matches!(winit.focused_mode, UpdateMode::Continuous) {
+        *samples = default();
+        return;
+    }
+
+    samples.0 += 1;
+    samples.1 += time.delta();
+    if samples.0 < SAMPLE_FRAMES {
+        return;
+    }
+    let average = samples.1 / samples.0;
+    *samples = default();
+    if average <= budget.0 {
+        return;
+    }
+    if let Some(lower) = quality.lower() {
+        *quality = lower;
+        info!("Frames take {average:?} on average, quality lowered to {lower:?}");
+    }
+}
+
+fn raise_quality(keys: Res<ButtonInput<KeyCode>>, mut quality: ResMut<Quality>) {
+    if !keys.just_pressed(KeyCode::KeyQ) {
+        return;
+    }
+    if let Some(higher) = quality.higher() {
+        *quality = higher;
+        info!("Quality raised to {higher:?}");
+    }
+}
+
+fn setup_quality_indicator(mut commands: Commands) {
+    commands.spawn((
+        Text::default(),
+        TextFont::from_font_size(16.0),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            right: Val::Px(8.0),
+            ..default()
+        },
+        QualityIndicator,
+    ));
+}
+
+fn update_quality_indicator(
+    quality: Res<Quality>,
+    photo: Option<Res<PhotoMode>>,
+    mut indicator_q: Query<(&mut Text, &mut Visibility), With<QualityIndicator>>,
+) {
+    for (mut text, mut visibility) in indicator_q.iter_mut() {
+        if quality.is_changed() {
+            text.0 = format!("Quality: {:?}", *quality);
+        }
+        // Kept out of the photos
+        visibility.set_if_neq(match photo {
+            Some(_) => Visibility::Hidden,
+            None => Visibility::Inherited,
+        });
+    }
+}
@@ -0,0 +1,176 @@
+use crate::chronicle::Headline;
+use crate::config::SimulationConfig;
+use crate::rng::SimRng;
+use crate::tick::Bookkeeping;
+use crate::weather::move_weather_fronts;
+use crate::zombie_state::{Status, ZombieState};
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+const EXPLOSION_RADIUS: i32 = 2;
+const EXPLOSION_KILL_PERCENT: i32 = 50;
+const EXPLOSION_NOISE: i32 = 300;
+const BROADCAST_NOISE: i32 = 1000;
+const COLD_SNAP_DROP: i32 = 40; // temperature units, terrain temperature spans -100..100
+const COLD_SNAP_TICKS: u32 = 100;
+
+/// Occasionally rolls an event from the weighted table in [`SimulationConfig::event_weights`]
+/// and applies it to the map, keeping long runs from settling into a stalemate.
+pub struct RandomEventsPlugin;
+
+impl Plugin for RandomEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ColdSnap>().add_systems(
+            Update,
+            (roll_random_events, end_cold_snap)
+                .chain()
+                .in_set(Bookkeeping)
+                // Both draw from the rng, always in the same order
+                .after(move_weather_fronts),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RandomEvent {
+    FuelDepotExplosion, // kills on both sides around a random cell, the blast draws zombies
+    SurvivorBroadcast,  // a human cell goes on the air, every horde around hears it
+    ColdSnap,           // the whole map gets colder for a while
+}
+
+/// A cold snap under way, its drop is taken off the cells' temperature until it's over.
+#[derive(Resource, Default, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ColdSnap {
+    pub ticks_left: u32,
+}
+
+fn roll_random_events(
+    mut ticks: Local<u32>,
+    rules: Res<SimulationConfig>,
+    mut rng: ResMut<SimRng>,
+    mut cold_snap: ResMut<ColdSnap>,
+    mut cells_q: Query<&mut ZombieState>,
+    mut headlines: EventWriter<Headline>,
+) {
+    *ticks += 1;
+    if !ticks.is_multiple_of(rules.event_roll_every_ticks)
+        || rng.0.random_range(0..100) >= rules.event_chance_percent
+    {
+        return;
+    }
+    let weights = rules.event_weights;
+    let table = [
+        (
+            RandomEvent::FuelDepotExplosion,
+            weights.fuel_depot_explosion,
+        ),
+        (RandomEvent::SurvivorBroadcast, weights.survivor_broadcast),
+        (RandomEvent::ColdSnap, weights.cold_snap),
+    ];
+    let Some(event) = pick(&mut rng.0, &table) else {
+        return;
+    };
+
+    match event {
+        RandomEvent::FuelDepotExplosion => {
+            let cells = cells_q.iter().count();
+            if cells == 0 {
+                return;
+            }
+            let Some(center) = cells_q
+                .iter()
+                .nth(rng.0.random_range(0..cells))
+                .map(|state| state.xy)
+            else {
+                return;
+            };
+            let mut killed = 0;
+            for mut state in cells_q.iter_mut() {
+                let distance = (state.xy - center).abs().max_element();
+                if distance > EXPLOSION_RADIUS {
+                    continue;
+                }
+                let dead = state.population * EXPLOSION_KILL_PERCENT / 100;
+                killed += dead;
+                state.population -= dead;
+                if state.population == 0 {
+                    state.status = Status::Empty;
+                    state.lineage = 0;
+                }
+                if distance == 0 {
+                    state.noise = state.noise.max(EXPLOSION_NOISE);
+                }
+            }
+            headlines.send(Headline(format!(
+                "a fuel depot exploded at {center}, {killed} killed"
+            )));
+        }
+        RandomEvent::SurvivorBroadcast => {
+            let humans = cells_q
+                .iter()
+                .filter(|state| state.status.is_human())
+                .count();
+            if humans == 0 {
+                return;
+            }
+            let index = rng.0.random_range(0..humans);
+            if let Some(mut state) = cells_q
+                .iter_mut()
+                .filter(|state| state.status.is_human())
+                .nth(index)
+            {
+                state.noise = state.noise.max(BROADCAST_NOISE);
+                headlines.send(Headline(format!(
+                    "survivors at {} are broadcasting, the hordes are listening",
+                    state.xy
+                )));
+            }
+        }
+        RandomEvent::ColdSnap => {
+            // A snap during a snap only makes it last longer
+            if cold_snap.ticks_left == 0 {
+                for mut state in cells_q.iter_mut() {
+                    state.temperature -= COLD_SNAP_DROP;
+                }
+            }
+            cold_snap.ticks_left = COLD_SNAP_TICKS;
+            headlines.send(Headline(format!(
+                "a cold snap hit, for {COLD_SNAP_TICKS} ticks"
+            )));
+        }
+    }
+}
+
+fn end_cold_snap(
+    mut cold_snap: ResMut<ColdSnap>,
+    mut cells_q: Query<&mut ZombieState>,
+    mut headlines: EventWriter<Headline>,
+) {
+    if cold_snap.ticks_left == 0 {
+        return;
+    }
+    cold_snap.ticks_left -= 1;
+    if cold_snap.ticks_left == 0 {
+        for mut state in cells_q.iter_mut() {
+            state.temperature += COLD_SNAP_DROP;
+        }
+        headlines.send(Headline("the cold snap is over".to_string()));
+    }
+}
+
+/// Weighted pick from the table.
+fn pick(rng: &mut impl Rng, table: &[(RandomEvent, u32)]) -> Option<RandomEvent> {
+    let total: u32 = table.iter().map(|(_, weight)| weight).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut roll = rng.random_range(0..total);
+    for &(event, weight) in table {
+        if roll < weight {
+            return Some(event);
+        }
+        roll -= weight;
+    }
+    None
+}
@@ -0,0 +1,152 @@
+use crate::outcome::{self, Outcome};
+use crate::stats::{LedgerTotals, WorldStats, sample_world_stats};
+use crate::tick::Bookkeeping;
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Tracks the run and writes a final summary (duration, ticks, outcome, peak populations)
+/// when the app exits, either by closing the window or by Ctrl+C in the terminal.
+pub struct FinalReportPlugin {
+    pub path: Option<PathBuf>, // also write the summary to this file
+}
+
+impl Plugin for FinalReportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GlobalLedger>()
+            .insert_resource(RunReport {
+                path: self.path.clone(),
+                started: Instant::now(),
+                ..default()
+            })
+            .add_systems(
+                Update,
+                (sum_ledgers, track_run)
+                    .in_set(Bookkeeping)
+                    .after(sample_world_stats),
+            )
+            .add_systems(Last, write_report_on_exit);
+    }
+}
+
+/// Sum of all cell ledgers since the start of the run.
+#[derive(Resource, Default, Debug)]
+pub struct GlobalLedger {
+    pub total: LedgerTotals,
+}
+
+#[derive(Resource)]
+struct RunReport {
+    path: Option<PathBuf>,
+    started: Instant,
+    settlements_founded: Option<usize>, // as the first tick found them
+}
+
+impl Default for RunReport {
+    fn default() -> Self {
+        RunReport {
+            path: None,
+            started: Instant::now(),
+            settlements_founded: None,
+        }
+    }
+}
+
+impl RunReport {
+    /// The outcome's summary, if the run ended with one, between the report's own lines.
+    fn summary(&self, outcome: Option<Outcome>, stats: &WorldStats) -> String {
+        let elapsed = self.started.elapsed().as_secs();
+        // The first tick it was reached
+        let peak_camps = stats
+            .history()
+            .iter()
+            .rev()
+            .max_by_key(|sample| sample.camps)
+            .map_or((0, 0), |sample| (sample.camps, sample.tick));
+        format!(
+            "Final report\n\
+             duration: {}h {}m {}s\n",
+            elapsed / 3600,
+            elapsed / 60 % 60,
+            elapsed % 60,
+        ) + &outcome::summary(outcome, stats.history())
+            + &format!(
+                "peak refugee camps: {} at tick {}\n",
+                peak_camps.0, peak_camps.1,
+            )
+    }
+}
+
+fn sum_ledgers(stats: Res<WorldStats>, mut global: ResMut<GlobalLedger>) {
+    if let Some(latest) = stats.latest() {
+        global.total.add(&latest.ledger, 1);
+    }
+}
+
+fn track_run(stats: Res<WorldStats>, mut report: ResMut<RunReport>) {
+    if report.settlements_founded.is_none() && stats.latest().is_some() {
+        report.settlements_founded = Some(stats.settlements().len());
+    }
+}
+
+/// Which founding settlements still have living descendants, largest first.
+fn diaspora(stats: &WorldStats, founded: usize) -> String {
+    let mut survivors: Vec<_> = stats.settlements().iter().collect();
+    survivors.sort_by_key(|(_, descendants)| std::cmp::Reverse(descendants.population));
+
+    let mut text = format!(
+        "settlements with surviving descendants: {} of {founded}\n",
+        survivors.len()
+    );
+    for (lineage, descendants) in survivors.iter().take(10) {
+        text += &format!(
+            "  settlement {lineage}: {} people in {} cells\n",
+            descendants.population, descendants.cells
+        );
+    }
+    text
+}
+
+fn write_report_on_exit(
+    mut exit_events: EventReader<AppExit>,
+    report: Res<RunReport>,
+    outcome: Option<Res<Outcome>>,
+    stats: Res<WorldStats>,
+    global: Res<GlobalLedger>,
+) {
+    if exit_events.read().last().is_none() {
+        return;
+    }
+
+    let total = global.total;
+    let summary = report.summary(outcome.as_deref().copied(), &stats)
+        + &format!(
+            "births: {}\n\
+             humans killed in combat: {} ({} of them turned)\n\
+             zombies destroyed in combat: {}\n\
+             disease deaths: {}\n\
+             zombies rotted in the heat: {}\n\
+             humans starved while cut off: {}\n\
+             lost crossing steep ground: {}\n\
+             emigrated off the map: {}\n\
+             humans lost to famine: {}\n",
+            total.births,
+            total.human_deaths,
+            total.conversions,
+            total.zombie_deaths,
+            total.disease_deaths,
+            total.decayed,
+            total.starved,
+            total.stragglers,
+            total.emigrated,
+            total.famine,
+        )
+        + &diaspora(&stats, report.settlements_founded.unwrap_or_default());
+    info!("{summary}");
+    if let Some(path) = &report.path
+        && let Err(err) = std::fs::write(path, summary)
+    {
+        error!("Failed to write final report to {path:?}: {err}");
+    }
+}
@@ -0,0 +1,27 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+/// Randomness of the simulation, seeded from the world seed so that two runs with the
+/// same seed play out the same. Systems with stochastic rules draw from it, in a fixed order:
+/// the cells are spawned before the first tick, then every tick moves the weather fronts before
+/// rolling the random events.
+#[derive(Resource)]
+pub struct SimRng(pub StdRng);
+
+impl SimRng {
+    pub fn new(seed: u64) -> Self {
+        SimRng(StdRng::seed_from_u64(seed))
+    }
+}
+
+/// Well mixed bits of `x` (splitmix64), for cheap seeded variation.
+///
+/// The cell update runs in parallel with no access to [`SimRng`], its rolls are
+/// scrambled from the cell's own state instead.
+pub fn scramble(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
@@ -0,0 +1,98 @@
+use crate::Terrain;
+use crate::config::{MapConfig, SimulationConfig};
+use crate::loading::LoadingState;
+use crate::overlays::map_image;
+use crate::zombie_state::{Status, ZombieState};
+use bevy::prelude::*;
+
+const BLOCK: usize = 4; // cells per texel side
+
+/// Low resolution summary of the world as one image, red for the zombies, green for the humans
+/// (both by how full the cells are) and blue for the altitude. Kept up to date for every view
+/// that wants it, like the minimap, so they don't each scan the cells.
+pub struct SatellitePlugin;
+
+impl Plugin for SatellitePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(LoadingState::BuildingViews), setup_satellite_view)
+            .add_systems(
+                Update,
+                update_satellite_view.run_if(in_state(LoadingState::Ready)),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct SatelliteView {
+    pub image: Handle<Image>,
+    pub size: UVec2, // in texels
+}
+
+fn setup_satellite_view(
+    map: Res<MapConfig>,
+    mut images: ResMut<Assets<Image>>,
+    mut commands: Commands,
+) {
+    let size = UVec2::new(
+        map.width.div_ceil(BLOCK) as u32,
+        map.height.div_ceil(BLOCK) as u32,
+    );
+    let image = images.add(map_image(size.x as usize, size.y as usize, |_, _| {
+        [0, 0, 0, 255]
+    }));
+    commands.insert_resource(SatelliteView { image, size });
+}
+
+fn update_satellite_view(
+    map: Res<MapConfig>,
+    rules: Res<SimulationConfig>,
+    terrain: Res<Terrain>,
+    satellite: Res<SatelliteView>,
+    changed_q: Query<(), Changed<ZombieState>>,
+    cells_q: Query<&ZombieState>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if changed_q.is_empty() && !terrain.is_changed() {
+        return;
+    }
+    let (width, height) = (satellite.size.x as usize, satellite.size.y as usize);
+    let block = |xy: IVec2| (xy.y as usize / BLOCK) * width + xy.x as usize / BLOCK;
+
+    let mut cells = vec![0; width * height];
+    let mut humans = vec![0; width * height];
+    let mut zombies = vec![0; width * height];
+    let mut altitude = vec![0.0; width * height];
+    for state in cells_q.iter() {
+        let i = block(state.xy);
+        cells[i] += 1;
+        match state.status {
+            Status::Human | Status::Military => humans[i] += state.population,
+            Status::Zombie => zombies[i] += state.population,
+            Status::Empty => {}
+        }
+    }
+    for (y, row) in terrain.0.iter().enumerate().take(map.height) {
+        for (x, layers) in row.iter().enumerate().take(map.width) {
+            altitude[block(IVec2::new(x as i32, y as i32))] += layers[0];
+        }
+    }
+
+    let Some(image) = images.get_mut(&satellite.image) else {
+        return;
+    };
+    *image = map_image(width, height, |x, y| {
+        let i = y * width + x;
+        let count = cells[i].max(1);
+        // Share of what the block's cells hold before overcrowding
+        let density = |population: i32| {
+            (population as f32 / (count * rules.cell_capacity.max(1)) as f32).clamp(0.0, 1.0)
+        };
+        let elevation = ((altitude[i] / count as f32 + 1.0) / 2.0).clamp(0.0, 1.0);
+        [
+            (density(zombies[i]) * 255.0) as u8,
+            (density(humans[i]) * 255.0) as u8,
+            (elevation * 255.0) as u8,
+            255,
+        ]
+    });
+}
@@ -0,0 +1,175 @@
+use crate::config::MapConfig;
+use crate::cure::CureProgress;
+use crate::daynight::TimeOfDay;
+use crate::error::{Error, Result};
+use crate::random_events::ColdSnap;
+use crate::seasons::SeasonCycle;
+use crate::weather::{Front, WeatherFronts};
+use crate::zombie_state::{ZombieState, violation};
+#[cfg(feature = "gui")]
+use crate::{Terrain, help::KeybindingsAppExt, loading::LoadingState, toast::Toast};
+#[cfg(feature = "gui")]
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "gui")]
+const SAVE_PATH: &str = "save.ron";
+
+/// F5 saves the whole grid, its terrain and the rest of the rules' state to `save.ron`,
+/// `--load <file>` starts from a save instead of generating a new map.
+#[cfg(feature = "gui")]
+pub struct SaveLoadPlugin;
+
+#[cfg(feature = "gui")]
+impl Plugin for SaveLoadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_keybindings("Save", &[("F5", "save the map to save.ron")])
+            .add_systems(
+                OnEnter(LoadingState::SpawningCells),
+                resume_rules.run_if(resource_exists::<SaveFile>),
+            )
+            .add_systems(
+                Update,
+                save_on_keypress.run_if(in_state(LoadingState::Ready)),
+            );
+    }
+}
+
+/// Everything needed to resume a run, present as a resource until the map is spawned from it.
+///
+/// The cells pick up where they left off, and so do the time of day, the season, the weather
+/// fronts, the cure research and a cold snap under way. A resumed run still starts over in some
+/// ways:
+/// - its random rolls are seeded anew from the map seed;
+/// - the stats, the final report and the chronicle only count from the resume on.
+#[derive(Resource, Serialize, Deserialize)]
+pub struct SaveFile {
+    pub map: MapConfig,
+    pub terrain: Vec<Vec<Vec<f32>>>, // like Terrain
+    pub cells: Vec<ZombieState>,     // row by row, from the bottom one
+    #[serde(default)]
+    pub rules: RulesState,
+}
+
+/// What the rules keep outside the cells.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct RulesState {
+    pub time_of_day: TimeOfDay,
+    pub season: SeasonCycle,
+    pub cure: CureProgress,
+    pub fronts: Vec<Front>,
+    pub cold_snap: ColdSnap,
+}
+
+/// The rules' resources as they are now, see [`RulesState`].
+#[cfg(feature = "gui")]
+#[derive(SystemParam)]
+struct CurrentRules<'w> {
+    time_of_day: Res<'w, TimeOfDay>,
+    season: Res<'w, SeasonCycle>,
+    cure: Res<'w, CureProgress>,
+    fronts: Res<'w, WeatherFronts>,
+    cold_snap: Res<'w, ColdSnap>,
+}
+
+#[cfg(feature = "gui")]
+impl CurrentRules<'_> {
+    fn state(&self) -> RulesState {
+        RulesState {
+            time_of_day: *self.time_of_day,
+            season: *self.season,
+            cure: *self.cure,
+            fronts: self.fronts.0.clone(),
+            cold_snap: *self.cold_snap,
+        }
+    }
+}
+
+/// Reads the save at `path`.
+pub fn read(path: &str) -> Result<SaveFile> {
+    let save: SaveFile = ron::from_str(&std::fs::read_to_string(path)?)?;
+    if save.cells.len() != save.map.width * save.map.height
+        || save.terrain.len() != save.map.height
+        || save.terrain.iter().flatten().any(|layers| layers.len() < 3)
+    {
+        return Err(Error::Invalid(
+            "cells or terrain don't match the map size".to_string(),
+        ));
+    }
+    // All the broken cells at once
+    let problems: Vec<String> = save
+        .cells
+        .iter()
+        .enumerate()
+        .filter_map(|(index, state)| {
+            let expected = IVec2::new(
+                (index % save.map.width) as i32,
+                (index / save.map.width) as i32,
+            );
+            if state.xy != expected {
+                return Some(format!(
+                    "cell {index} is at {}, expected {expected}",
+                    state.xy
+                ));
+            }
+            violation(state).map(|message| format!("cell {}: {message}", state.xy))
+        })
+        .collect();
+    if !problems.is_empty() {
+        return Err(Error::Invalid(format!(
+            "{} broken cells\n{}",
+            problems.len(),
+            problems.join("\n")
+        )));
+    }
+    // Runs before the app and its logging are set up
+    println!("Save loaded from {path}");
+    Ok(save)
+}
+
+#[cfg(feature = "gui")]
+fn write(path: &str, save: &SaveFile) -> Result<()> {
+    std::fs::write(path, ron::to_string(save)?)?;
+    Ok(())
+}
+
+#[cfg(feature = "gui")]
+fn save_on_keypress(
+    keys: Res<ButtonInput<KeyCode>>,
+    map: Res<MapConfig>,
+    terrain: Res<Terrain>,
+    cells_q: Query<&ZombieState>,
+    rules: CurrentRules,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !keys.just_pressed(KeyCode::F5) {
+        return;
+    }
+
+    let mut cells: Vec<ZombieState> = cells_q.iter().cloned().collect();
+    cells.sort_by_key(|state| (state.xy.y, state.xy.x));
+    let save = SaveFile {
+        map: map.clone(),
+        terrain: terrain.0.clone(),
+        cells,
+        rules: rules.state(),
+    };
+    match write(SAVE_PATH, &save) {
+        Ok(()) => info!("Saved to {SAVE_PATH}"),
+        Err(err) => {
+            error!("Failed to save to {SAVE_PATH}: {err}");
+            toasts.send(Toast(format!("Failed to save to {SAVE_PATH}: {err}")));
+        }
+    }
+}
+
+/// Picks the rules up where the save left them, before the cells are spawned from it.
+pub fn resume_rules(save: Res<SaveFile>, mut commands: Commands) {
+    let rules = save.rules.clone();
+    commands.insert_resource(rules.time_of_day);
+    commands.insert_resource(rules.season);
+    commands.insert_resource(rules.cure);
+    commands.insert_resource(WeatherFronts(rules.fronts));
+    commands.insert_resource(rules.cold_snap);
+}
@@ -0,0 +1,78 @@
+use crate::chronicle::Headline;
+use crate::config::SimulationConfig;
+use crate::tick::Bookkeeping;
+use crate::zombie_state::ZombieState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Year of four seasons advanced every tick, warming the cells in summer and chilling them in
+/// winter, so the cold slows the hordes down and the fields grow less.
+pub struct SeasonsPlugin;
+
+impl Plugin for SeasonsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SeasonCycle>()
+            .add_systems(Update, advance_season.in_set(Bookkeeping));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Season {
+    Spring, // the terrain's own temperature, as the map starts
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    /// Added to the terrain temperature of every cell.
+    pub fn temperature_offset(&self, swing: i32) -> i32 {
+        match self {
+            Season::Spring | Season::Autumn => 0,
+            Season::Summer => swing,
+            Season::Winter => -swing,
+        }
+    }
+}
+
+/// Ticks since the first spring.
+#[derive(Resource, Default, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SeasonCycle(pub u64);
+
+impl SeasonCycle {
+    /// Always spring without seasons (`season_ticks` of 0).
+    pub fn season(&self, season_ticks: u32) -> Season {
+        if season_ticks == 0 {
+            return Season::Spring;
+        }
+        match (self.0 / season_ticks as u64) % 4 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+}
+
+fn advance_season(
+    rules: Res<SimulationConfig>,
+    mut cycle: ResMut<SeasonCycle>,
+    mut cells_q: Query<&mut ZombieState>,
+    mut headlines: EventWriter<Headline>,
+) {
+    let before = cycle.season(rules.season_ticks);
+    cycle.0 += 1;
+    let after = cycle.season(rules.season_ticks);
+    if before == after {
+        return;
+    }
+    // Shifted rather than set, so a cold snap going on stays on top of it
+    let shift = after.temperature_offset(rules.season_temperature_swing)
+        - before.temperature_offset(rules.season_temperature_swing);
+    if shift != 0 {
+        for mut state in cells_q.iter_mut() {
+            state.temperature += shift;
+        }
+    }
+    headlines.send(Headline(format!("{after:?} has come").to_lowercase()));
+}
@@ -0,0 +1,52 @@
+use crate::config::MapConfig;
+use crate::loading::LoadingState;
+use crate::overlays::{map_image, map_sprite};
+use crate::zombie_state::ZombieState;
+use bevy::prelude::*;
+
+/// Subtle tint over secured territory, always shown.
+pub struct SecuredPlugin;
+
+impl Plugin for SecuredPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(LoadingState::BuildingViews), setup_secured_view)
+            .add_systems(
+                Update,
+                update_secured_view.run_if(in_state(LoadingState::Ready)),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct SecuredImage(Handle<Image>);
+
+fn setup_secured_view(
+    map: Res<MapConfig>,
+    mut images: ResMut<Assets<Image>>,
+    mut commands: Commands,
+) {
+    let image = images.add(map_image(map.width, map.height, |_, _| [0, 0, 0, 0]));
+    commands.spawn(map_sprite(&map, image.clone(), 1.2));
+    commands.insert_resource(SecuredImage(image));
+}
+
+fn update_secured_view(
+    map: Res<MapConfig>,
+    cells_q: Query<&ZombieState>,
+    secured_image: Res<SecuredImage>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let mut secured = vec![false; map.width * map.height];
+    for state in cells_q.iter() {
+        secured[state.xy.y as usize * map.width + state.xy.x as usize] = state.secured;
+    }
+    if let Some(image) = images.get_mut(&secured_image.0) {
+        *image = map_image(map.width, map.height, |x, y| {
+            if secured[y * map.width + x] {
+                [120, 220, 140, 50]
+            } else {
+                [0, 0, 0, 0]
+            }
+        });
+    }
+}
@@ -0,0 +1,133 @@
+use crate::config::Settlements;
+use crate::terrain::Features;
+use crate::zombie_state::Status;
+use bevy::prelude::*;
+use rand::Rng;
+
+const OUTBREAK_TRIES: u32 = 100; // random cells looked at for each outbreak
+const OUTBREAK_CLEARANCE: i32 = 3; // cells kept between an outbreak and the edge of a city
+
+/// Who a cell starts with.
+#[derive(Clone, Debug, Default)]
+pub struct Settler {
+    pub status: Status,
+    pub population: i32,
+    pub lineage: u32, // of the city or outbreak, numbered after its center cell
+    pub faction: u8,  // of the humans, 0 for zombies and empty cells
+}
+
+/// Places a city at every city site of the terrain, `outbreaks` zombie outbreaks at random
+/// land away from them and `army_bases` bases on any land, all thinning out from their center.
+/// The cities and bases are dealt out to the `factions` in turn. `[y][x]` like the features.
+pub fn seed_settlements(
+    settlements: &Settlements,
+    features: &Features,
+    rng: &mut impl Rng,
+) -> Vec<Vec<Settler>> {
+    let kinds = &features.kinds;
+    let (width, height) = (kinds[0].len(), kinds.len());
+    let mut settlers = vec![vec![Settler::default(); width]; height];
+
+    // A map without city sites still gets a city somewhere
+    let mut cities: Vec<IVec2> = features
+        .cities
+        .iter()
+        .map(|&(x, y)| IVec2::new(x as i32, y as i32))
+        .collect();
+    if cities.is_empty() {
+        cities.extend(random_land(features, rng, |_| true));
+    }
+    let factions = settlements.factions.max(1);
+    for (i, &center) in cities.iter().enumerate() {
+        settle(
+            &mut settlers,
+            features,
+            center,
+            (Status::Human, i as u8 % factions + 1),
+            settlements.city_radius,
+            settlements.city_population,
+        );
+    }
+
+    let away_from_cities = |xy: IVec2| {
+        cities.iter().all(|city| {
+            (xy - *city).abs().max_element()
+                > settlements.city_radius + settlements.outbreak_radius + OUTBREAK_CLEARANCE
+        })
+    };
+    for _ in 0..settlements.outbreaks {
+        if let Some(center) = random_land(features, rng, away_from_cities) {
+            settle(
+                &mut settlers,
+                features,
+                center,
+                (Status::Zombie, 0),
+                settlements.outbreak_radius,
+                settlements.outbreak_population,
+            );
+        }
+    }
+    for i in 0..settlements.army_bases {
+        if let Some(center) = random_land(features, rng, |_| true) {
+            settle(
+                &mut settlers,
+                features,
+                center,
+                (Status::Military, i as u8 % factions + 1),
+                settlements.army_base_radius,
+                settlements.army_base_population,
+            );
+        }
+    }
+    settlers
+}
+
+/// Fills the passable cells within `radius` of `center`, the population falling off linearly
+/// to the last ring. Overlapping groups keep the bigger population.
+fn settle(
+    settlers: &mut [Vec<Settler>],
+    features: &Features,
+    center: IVec2,
+    (status, faction): (Status, u8),
+    radius: i32,
+    peak: i32,
+) {
+    let (width, height) = (settlers[0].len() as i32, settlers.len() as i32);
+    let lineage = (center.y * width + center.x + 1) as u32;
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let xy = center + IVec2::new(dx, dy);
+            if xy.x < 0 || xy.y < 0 || xy.x >= width || xy.y >= height {
+                continue;
+            }
+            let (x, y) = (xy.x as usize, xy.y as usize);
+            if features.kinds[y][x].is_impassable() {
+                continue;
+            }
+            let distance = dx.abs().max(dy.abs());
+            let population = (peak * (radius + 1 - distance) / (radius + 1)).max(1);
+            if population > settlers[y][x].population {
+                settlers[y][x] = Settler {
+                    status: status.clone(),
+                    population,
+                    lineage,
+                    faction,
+                };
+            }
+        }
+    }
+}
+
+fn random_land(
+    features: &Features,
+    rng: &mut impl Rng,
+    allowed: impl Fn(IVec2) -> bool,
+) -> Option<IVec2> {
+    let (width, height) = (features.kinds[0].len(), features.kinds.len());
+    (0..OUTBREAK_TRIES)
+        .map(|_| (rng.random_range(0..width), rng.random_range(0..height)))
+        .find(|&(x, y)| {
+            !features.kinds[y][x].is_impassable() && allowed(IVec2::new(x as i32, y as i32))
+        })
+        .map(|(x, y)| IVec2::new(x as i32, y as i32))
+}
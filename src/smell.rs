@@ -0,0 +1,79 @@
+use crate::config::{MapConfig, SimulationConfig};
+use crate::loading::LoadingState;
+use crate::overlays::{Overlays, map_image, map_sprite};
+use crate::quality::Quality;
+use crate::zombie_state::{SMELL_SCALE, ZombieState};
+use bevy::prelude::*;
+
+/// Overlay (M) tinting the ground red where the zombies can be smelled, at Quality::High only.
+pub struct SmellOverlayPlugin;
+
+impl Plugin for SmellOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(LoadingState::BuildingViews), setup_smell_view)
+            .add_systems(
+                Update,
+                (show_smell_view, update_smell_view.run_if(smell_shown))
+                    .run_if(in_state(LoadingState::Ready)),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct SmellImage(Handle<Image>);
+
+#[derive(Component)]
+struct SmellView;
+
+fn setup_smell_view(
+    map: Res<MapConfig>,
+    mut images: ResMut<Assets<Image>>,
+    mut commands: Commands,
+) {
+    let image = images.add(map_image(map.width, map.height, |_, _| [0, 0, 0, 0]));
+    commands.spawn((
+        map_sprite(&map, image.clone(), 1.3),
+        Visibility::Hidden,
+        SmellView,
+    ));
+    commands.insert_resource(SmellImage(image));
+}
+
+fn smell_shown(overlays: Res<Overlays>, quality: Res<Quality>) -> bool {
+    overlays.smell && *quality == Quality::High
+}
+
+fn show_smell_view(
+    overlays: Res<Overlays>,
+    quality: Res<Quality>,
+    mut view_q: Query<&mut Visibility, With<SmellView>>,
+) {
+    let shown = smell_shown(overlays, quality);
+    for mut visibility in view_q.iter_mut() {
+        *visibility = if shown {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn update_smell_view(
+    map: Res<MapConfig>,
+    rules: Res<SimulationConfig>,
+    cells_q: Query<&ZombieState>,
+    smell_image: Res<SmellImage>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let mut smell = vec![0; map.width * map.height];
+    for state in cells_q.iter() {
+        smell[state.xy.y as usize * map.width + state.xy.x as usize] = state.smell_zombie;
+    }
+    let full = (rules.smell_max * SMELL_SCALE).max(1) as f32;
+    if let Some(image) = images.get_mut(&smell_image.0) {
+        *image = map_image(map.width, map.height, |x, y| {
+            let scent = (smell[y * map.width + x] as f32 / full).clamp(0.0, 1.0);
+            [255, 0, 0, (scent * 255.0) as u8]
+        });
+    }
+}
@@ -0,0 +1,159 @@
+use crate::config::MapConfig;
+use crate::rng::SimRng;
+use crate::tick::TickSet;
+use crate::zombie_state::{ZombieState, violation};
+use crate::{SimulationPlugins, WorldSeed, chronicle, generate_terrain, spawn_cells};
+use bevy::app::TerminalCtrlCHandlerPlugin;
+use bevy::prelude::*;
+use bevy_life::SimulationBatch;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+const TICKS_PER_SEED: u32 = 2_000;
+const HISTORY_LEN: usize = 16; // ticks of history kept for the bug report
+const NEIGHBORHOOD_RADIUS: i32 = 2;
+
+#[derive(Resource)]
+struct Soak {
+    seed: u64,
+    tick: u32,
+    history: VecDeque<Vec<ZombieState>>, // full grid snapshots, oldest first
+    report: Option<PathBuf>,
+}
+
+/// `--soak` mode: runs random seeds back-to-back headlessly until the process is stopped.
+///
+/// Any invariant violation (e.g. negative population) ends the run and is written to a
+/// `soak-bug-<seed>-<tick>.txt` report with the offending neighborhood and its recent history.
+/// Ctrl+C finishes the current tick and prints a summary of the session.
+pub fn run() {
+    let started = std::time::Instant::now();
+    let mut runs = 0;
+    let mut reports = 0;
+    let mut ticks = 0u64;
+    loop {
+        runs += 1;
+        let seed = rand::random::<u64>();
+        println!("Soak run {runs}, seed {seed} ({reports} bug reports so far)");
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(SimulationPlugins {
+                tick_time_step: None, // tick on every update, as fast as possible
+            })
+            // The rules' headlines go nowhere
+            .add_plugins(chronicle::ChroniclePlugin { path: None })
+            .insert_resource(SimulationBatch)
+            .insert_resource(MapConfig::default())
+            .insert_resource(WorldSeed(seed))
+            .insert_resource(SimRng::new(seed))
+            .insert_resource(Soak {
+                seed,
+                tick: 0,
+                history: VecDeque::with_capacity(HISTORY_LEN),
+                report: None,
+            })
+            .add_systems(Startup, (generate_terrain, spawn_cells).chain())
+            .add_systems(Update, check_invariants.in_set(TickSet::PostDecision));
+        if !app.is_plugin_added::<TerminalCtrlCHandlerPlugin>() {
+            app.add_plugins(TerminalCtrlCHandlerPlugin);
+        }
+        app.finish();
+        app.cleanup();
+
+        for _ in 0..TICKS_PER_SEED {
+            app.update();
+            ticks += 1;
+            if app.should_exit().is_some() {
+                println!(
+                    "Soak stopped after {:.0}s: {runs} runs, {ticks} ticks, {reports} bug reports",
+                    started.elapsed().as_secs_f64()
+                );
+                return;
+            }
+            if let Some(path) = &app.world().resource::<Soak>().report {
+                println!("Invariant violated, bug report written to {path:?}");
+                reports += 1;
+                break;
+            }
+        }
+    }
+}
+
+fn check_invariants(map: Res<MapConfig>, cells_q: Query<&ZombieState>, mut soak: ResMut<Soak>) {
+    let mut grid = vec![ZombieState::default(); map.width * map.height];
+    for state in cells_q.iter() {
+        grid[index(&map, state.xy)] = state.clone();
+    }
+
+    soak.tick += 1;
+    if soak.history.len() == HISTORY_LEN {
+        soak.history.pop_front();
+    }
+    soak.history.push_back(grid);
+
+    let grid = soak.history.back().unwrap();
+    let found = grid
+        .iter()
+        .find_map(|state| violation(state).map(|message| (state.xy, message)))
+        .or_else(|| cure_lost(grid));
+
+    if let Some((xy, message)) = found {
+        let path = PathBuf::from(format!("soak-bug-{}-{}.txt", soak.seed, soak.tick));
+        if let Err(err) = std::fs::write(&path, bug_report(&map, &soak, xy, &message)) {
+            println!("Failed to write bug report {path:?}: {err}");
+        }
+        soak.report = Some(path);
+    }
+}
+
+/// The zombies cured over the map if the humans didn't take in as many, with one of the cells
+/// bringing them back.
+fn cure_lost(grid: &[ZombieState]) -> Option<(IVec2, String)> {
+    let cured: i64 = grid.iter().map(|state| state.ledger.cured as i64).sum();
+    let recovered: i64 = grid.iter().map(|state| state.ledger.recovered as i64).sum();
+    (cured != recovered).then(|| {
+        let curing = grid.iter().find(|state| state.ledger.cured > 0);
+        (
+            curing.map_or(IVec2::ZERO, |state| state.xy),
+            format!(
+                "a share in {cured} zombies cured over the map, {recovered} taken in as humans"
+            ),
+        )
+    })
+}
+
+fn bug_report(map: &MapConfig, soak: &Soak, xy: IVec2, message: &str) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "Soak bug report");
+    let _ = writeln!(report, "seed: {}", soak.seed);
+    let _ = writeln!(report, "tick: {}", soak.tick);
+    let _ = writeln!(report, "violation: cell {xy} has {message}");
+
+    let first_tick = soak.tick + 1 - soak.history.len() as u32;
+    for (i, grid) in soak.history.iter().enumerate().rev() {
+        let _ = writeln!(
+            report,
+            "\n--- tick {} (neighborhood of radius {NEIGHBORHOOD_RADIUS}) ---",
+            first_tick + i as u32
+        );
+        for dy in -NEIGHBORHOOD_RADIUS..=NEIGHBORHOOD_RADIUS {
+            for dx in -NEIGHBORHOOD_RADIUS..=NEIGHBORHOOD_RADIUS {
+                let neighbor = xy + IVec2::new(dx, dy);
+                if in_bounds(map, neighbor) {
+                    let _ = writeln!(report, "{:?}", grid[index(map, neighbor)]);
+                }
+            }
+        }
+    }
+    report
+}
+
+fn in_bounds(map: &MapConfig, xy: IVec2) -> bool {
+    xy.x >= 0 && xy.y >= 0 && (xy.x as usize) < map.width && (xy.y as usize) < map.height
+}
+
+fn index(map: &MapConfig, xy: IVec2) -> usize {
+    xy.y as usize * map.width + xy.x as usize
+}
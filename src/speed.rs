@@ -0,0 +1,105 @@
+use crate::help::KeybindingsAppExt;
+use crate::photo::PhotoMode;
+use crate::tick::{TickClock, TickSet};
+use bevy::prelude::*;
+use bevy_life::SimulationPause;
+
+const SPEED_FACTOR: f64 = 2.0; // tick step change per + or - press
+const TICK_TIME_STEP_RANGE: (f64, f64) = (0.01, 5.0); // seconds
+
+/// Space pauses the simulation, `.` then runs a single tick, + and - make timed ticks
+/// faster or slower. Pausing puts in bevy_life's [`SimulationPause`].
+pub struct SpeedControlsPlugin {
+    pub tick_time_step: Option<f64>, // none ticks on every frame, at a speed that can't change
+}
+
+impl Plugin for SpeedControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_keybindings(
+            "Speed",
+            &[
+                ("Space", "pause or resume"),
+                (".", "run a single tick while paused"),
+            ],
+        );
+        if self.tick_time_step.is_some() {
+            app.add_keybindings("Speed", &[("+ / -", "faster or slower ticks")]);
+        }
+        app.insert_resource(SimulationSpeed {
+            paused: false,
+            tick_time_step: self.tick_time_step,
+        })
+        .add_systems(
+            Update,
+            (
+                control_speed.run_if(not(resource_exists::<PhotoMode>)),
+                // After a single step has ticked, to pause again
+                apply_speed.after(TickSet::PostDecision),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Pace of the simulation, changing it applies from the next frame.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SimulationSpeed {
+    pub paused: bool,
+    pub tick_time_step: Option<f64>, // seconds per tick, none ticks on every frame
+}
+
+fn control_speed(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut speed: ResMut<SimulationSpeed>,
+    mut clock: ResMut<TickClock>,
+    mut commands: Commands,
+) {
+    if keys.just_pressed(KeyCode::Space) {
+        speed.paused = !speed.paused;
+        info!(
+            "Simulation {}",
+            if speed.paused { "paused" } else { "resumed" }
+        );
+    }
+    if keys.just_pressed(KeyCode::Period) && speed.paused {
+        // Lifted for the next frame's tick, the cell update and everything around it
+        commands.remove_resource::<SimulationPause>();
+        clock.step();
+    }
+
+    let Some(tick_time_step) = speed.tick_time_step else {
+        return;
+    };
+    let factor = if keys.any_just_pressed([KeyCode::Equal, KeyCode::NumpadAdd]) {
+        1.0 / SPEED_FACTOR
+    } else if keys.any_just_pressed([KeyCode::Minus, KeyCode::NumpadSubtract]) {
+        SPEED_FACTOR
+    } else {
+        return;
+    };
+    let tick_time_step =
+        (tick_time_step * factor).clamp(TICK_TIME_STEP_RANGE.0, TICK_TIME_STEP_RANGE.1);
+    speed.tick_time_step = Some(tick_time_step);
+    info!("Tick time step: {tick_time_step}s");
+}
+
+fn apply_speed(
+    speed: Res<SimulationSpeed>,
+    pause: Option<Res<SimulationPause>>,
+    mut clock: ResMut<TickClock>,
+    mut commands: Commands,
+) {
+    if speed.is_changed()
+        && let Some(tick_time_step) = speed.tick_time_step
+    {
+        clock.set_time_step(tick_time_step);
+    }
+    if clock.is_stepping() {
+        return;
+    }
+    match (speed.paused, pause.is_some()) {
+        (true, false) => commands.insert_resource(SimulationPause),
+        (false, true) => commands.remove_resource::<SimulationPause>(),
+        _ => {}
+    }
+}
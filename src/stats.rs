@@ -0,0 +1,324 @@
+use crate::config::MapConfig;
+use crate::tick::Bookkeeping;
+use crate::zombie_state::{Ledger, ZombieState, direction_to_delta};
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+const RECOUNT_EVERY_TICKS: u64 = 500;
+
+/// Keeps [`WorldStats`] up to date, one sample after every tick.
+pub struct WorldStatsPlugin;
+
+impl Plugin for WorldStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldStats>()
+            .add_systems(Update, sample_world_stats.in_set(Bookkeeping));
+    }
+}
+
+/// Global totals of one tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TickStats {
+    pub tick: u64, // from 1
+    pub humans: i64,
+    pub zombies: i64,
+    pub conversions: i64, // humans killed that rose as zombies this tick
+    pub occupied_cells: usize,
+    pub human_cells: usize,
+    pub zombie_cells: usize,
+    pub land_cells: usize, // every cell but water and rivers, so the shares are of the same total each tick
+    pub largest_human_region: usize, // cells, human cells joined through any of the 8 neighbors
+    pub battles: usize,    // cells with combat losses on either side this tick
+    pub cells_captured: usize, // cells that changed hands between humans and zombies
+    pub secured_cells: usize,
+    pub emigrated: i64,       // walked off an open map edge this tick
+    pub camps: usize,         // overcrowded refugee camps
+    pub ledger: LedgerTotals, // of all cells this tick
+}
+
+/// Population change breakdown summed over cells.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LedgerTotals {
+    pub births: i64,
+    pub human_deaths: i64,
+    pub zombie_deaths: i64,
+    pub conversions: i64,
+    pub disease_deaths: i64,
+    pub decayed: i64,
+    pub starved: i64,
+    pub stragglers: i64,
+    pub emigrated: i64,
+    pub famine: i64,
+}
+
+impl LedgerTotals {
+    fn of(ledger: &Ledger) -> Self {
+        LedgerTotals {
+            births: ledger.births as i64,
+            human_deaths: ledger.human_deaths as i64,
+            zombie_deaths: ledger.zombie_deaths as i64,
+            conversions: ledger.conversions as i64,
+            disease_deaths: ledger.disease_deaths as i64,
+            decayed: ledger.decayed as i64,
+            starved: ledger.starved as i64,
+            stragglers: ledger.stragglers as i64,
+            emigrated: ledger.emigrated as i64,
+            famine: ledger.famine as i64,
+        }
+    }
+
+    /// Adds `other` times `sign`.
+    pub fn add(&mut self, other: &Self, sign: i64) {
+        self.births += other.births * sign;
+        self.human_deaths += other.human_deaths * sign;
+        self.zombie_deaths += other.zombie_deaths * sign;
+        self.conversions += other.conversions * sign;
+        self.disease_deaths += other.disease_deaths * sign;
+        self.decayed += other.decayed * sign;
+        self.starved += other.starved * sign;
+        self.stragglers += other.stragglers * sign;
+        self.emigrated += other.emigrated * sign;
+        self.famine += other.famine * sign;
+    }
+}
+
+/// The humans descending from one founding settlement, see [`ZombieState::lineage`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Descendants {
+    pub population: i64,
+    pub cells: usize,
+}
+
+impl TickStats {
+    /// Percentage of the land held by humans, zombies and neither.
+    pub fn control_percent(&self) -> (f32, f32, f32) {
+        let share = |cells: usize| cells as f32 * 100.0 / self.land_cells.max(1) as f32;
+        let (humans, zombies) = (share(self.human_cells), share(self.zombie_cells));
+        (humans, zombies, 100.0 - humans - zombies)
+    }
+}
+
+/// History of the global totals since the start of the run, for balancing.
+#[derive(Resource, Debug, Default)]
+pub struct WorldStats {
+    history: Vec<TickStats>, // oldest first
+    captures: u64,           // changes of hands of all cells so far
+    cells: Vec<CellCount>,   // as last counted, row by row
+    totals: CellCount,
+    settlements: HashMap<u32, Descendants>, // by lineage, of the human cells as last counted
+}
+
+impl WorldStats {
+    /// Totals after the last tick, if there was one.
+    pub fn latest(&self) -> Option<&TickStats> {
+        self.history.last()
+    }
+
+    /// Every tick so far, oldest first.
+    pub fn history(&self) -> &[TickStats] {
+        &self.history
+    }
+
+    /// The last `ticks` ticks, oldest first.
+    pub fn recent(&self, ticks: usize) -> &[TickStats] {
+        &self.history[self.history.len().saturating_sub(ticks)..]
+    }
+
+    /// The founding settlements with living descendants after the last tick, by lineage.
+    pub fn settlements(&self) -> &HashMap<u32, Descendants> {
+        &self.settlements
+    }
+
+    /// Moves the settlement `count` is in by `sign` times its humans.
+    fn add_settler(&mut self, count: &CellCount, sign: i64) {
+        if count.settlers == 0 && count.settler_cell == 0 {
+            return;
+        }
+        let descendants = self.settlements.entry(count.lineage).or_default();
+        descendants.population += count.settlers * sign;
+        descendants.cells = (descendants.cells as i64 + count.settler_cell * sign) as usize;
+        if descendants.cells == 0 {
+            self.settlements.remove(&count.lineage);
+        }
+    }
+}
+
+/// What one cell adds to the totals, kept per cell so a changed cell only moves the totals by its difference.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct CellCount {
+    humans: i64,
+    zombies: i64,
+    ledger: LedgerTotals,
+    captures: i64, // so far, not this tick
+    occupied: i64,
+    human: i64,
+    zombie: i64,
+    land: i64,
+    battle: i64,
+    secured: i64,
+    camp: i64,
+    lineage: u32,      // of the humans, soldiers aside
+    settlers: i64,     // those humans
+    settler_cell: i64, // 1 if there are any
+}
+
+impl CellCount {
+    fn of(state: &ZombieState) -> Self {
+        let population = state.population as i64;
+        CellCount {
+            humans: if state.status.is_living() {
+                population
+            } else {
+                0
+            },
+            zombies: if state.status.is_zombie() {
+                population
+            } else {
+                0
+            },
+            ledger: LedgerTotals::of(&state.ledger),
+            captures: state.captures as i64,
+            occupied: !state.status.is_empty() as i64,
+            human: state.status.is_living() as i64,
+            zombie: state.status.is_zombie() as i64,
+            land: !state.terrain.is_impassable() as i64,
+            battle: (state.ledger.human_deaths > 0 || state.ledger.zombie_deaths > 0) as i64,
+            secured: state.secured as i64,
+            camp: state.camp as i64,
+            lineage: state.lineage,
+            settlers: if state.status.is_human() {
+                population
+            } else {
+                0
+            },
+            settler_cell: state.status.is_human() as i64,
+        }
+    }
+
+    /// Adds `other` times `sign`, the settlements are kept apart.
+    fn add(&mut self, other: &Self, sign: i64) {
+        self.humans += other.humans * sign;
+        self.zombies += other.zombies * sign;
+        self.ledger.add(&other.ledger, sign);
+        self.captures += other.captures * sign;
+        self.occupied += other.occupied * sign;
+        self.human += other.human * sign;
+        self.zombie += other.zombie * sign;
+        self.land += other.land * sign;
+        self.battle += other.battle * sign;
+        self.secured += other.secured * sign;
+        self.camp += other.camp * sign;
+    }
+}
+
+/// Totals are kept up to date from the cells that changed since the last tick, with a full
+/// recount every [`RECOUNT_EVERY_TICKS`] in case anything slipped past the change detection.
+pub fn sample_world_stats(
+    map: Res<MapConfig>,
+    changed_q: Query<&ZombieState, Changed<ZombieState>>,
+    cells_q: Query<&ZombieState>,
+    mut stats: ResMut<WorldStats>,
+) {
+    let index = |xy: IVec2| xy.y as usize * map.width + xy.x as usize;
+    let tick = stats.history.len() as u64 + 1;
+    let stats = &mut *stats;
+
+    let counted = stats.cells.len() == map.width * map.height;
+    let mut humans_moved = !counted;
+    if counted {
+        for state in changed_q.iter() {
+            let count = CellCount::of(state);
+            let old = std::mem::replace(&mut stats.cells[index(state.xy)], count);
+            humans_moved |= old.human != count.human;
+            stats.totals.add(&old, -1);
+            stats.totals.add(&count, 1);
+            stats.add_settler(&old, -1);
+            stats.add_settler(&count, 1);
+        }
+    }
+    if !counted || tick.is_multiple_of(RECOUNT_EVERY_TICKS) {
+        let mut recount = WorldStats {
+            cells: vec![CellCount::default(); map.width * map.height],
+            ..default()
+        };
+        for state in cells_q.iter() {
+            let count = CellCount::of(state);
+            recount.totals.add(&count, 1);
+            recount.add_settler(&count, 1);
+            recount.cells[index(state.xy)] = count;
+        }
+        if counted
+            && (recount.totals != stats.totals
+                || recount.cells != stats.cells
+                || recount.settlements != stats.settlements)
+        {
+            warn!("World stats drifted, recounted at tick {tick}");
+            humans_moved = true;
+        }
+        (stats.totals, stats.cells, stats.settlements) =
+            (recount.totals, recount.cells, recount.settlements);
+    }
+
+    // The flood fill only reruns once the human cells are different
+    let largest_human_region = match (humans_moved, stats.latest()) {
+        (false, Some(latest)) => latest.largest_human_region,
+        _ => largest_region(
+            &map,
+            stats.cells.iter().map(|count| count.human > 0).collect(),
+        ),
+    };
+
+    let totals = stats.totals;
+    let sample = TickStats {
+        tick,
+        humans: totals.humans,
+        zombies: totals.zombies,
+        conversions: totals.ledger.conversions,
+        occupied_cells: totals.occupied as usize,
+        human_cells: totals.human as usize,
+        zombie_cells: totals.zombie as usize,
+        land_cells: totals.land as usize,
+        largest_human_region,
+        battles: totals.battle as usize,
+        cells_captured: (totals.captures as u64).saturating_sub(stats.captures) as usize,
+        secured_cells: totals.secured as usize,
+        emigrated: totals.ledger.emigrated,
+        camps: totals.camp as usize,
+        ledger: totals.ledger,
+    };
+    stats.captures = totals.captures as u64;
+
+    stats.history.push(sample);
+}
+
+/// Size of the largest group of connected cells in `cells`, flood filled away as it goes.
+fn largest_region(map: &MapConfig, mut cells: Vec<bool>) -> usize {
+    let (width, height) = (map.width as i32, map.height as i32);
+    let index = |xy: IVec2| (xy.y * width + xy.x) as usize;
+
+    let mut largest = 0;
+    let mut stack = Vec::new();
+    for start in 0..cells.len() {
+        if !cells[start] {
+            continue;
+        }
+        cells[start] = false;
+        stack.push(IVec2::new(start as i32 % width, start as i32 / width));
+        let mut size = 0;
+        while let Some(xy) = stack.pop() {
+            size += 1;
+            for direction in 0..8 {
+                let neighbor = xy + direction_to_delta(direction).unwrap();
+                if neighbor.x < 0 || neighbor.y < 0 || neighbor.x >= width || neighbor.y >= height {
+                    continue;
+                }
+                if cells[index(neighbor)] {
+                    cells[index(neighbor)] = false;
+                    stack.push(neighbor);
+                }
+            }
+        }
+        largest = largest.max(size);
+    }
+    largest
+}
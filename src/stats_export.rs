@@ -0,0 +1,102 @@
+use crate::stats::{WorldStats, sample_world_stats};
+use crate::tick::Bookkeeping;
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+/// Appends the [`WorldStats`] of every tick to a file for analysis after the run
+/// (enabled with `--stats <file>`): JSON lines if the file ends in `.jsonl`, CSV otherwise.
+pub struct StatsExportPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for StatsExportPlugin {
+    fn build(&self, app: &mut App) {
+        let json = self
+            .path
+            .extension()
+            .is_some_and(|extension| extension == "jsonl");
+        let mut writer = match File::create(&self.path) {
+            Ok(file) => BufWriter::new(file),
+            Err(err) => {
+                error!("Stats export disabled, can't create {:?}: {err}", self.path);
+                return;
+            }
+        };
+        if !json
+            && let Err(err) = writeln!(
+                writer,
+                "tick,humans,zombies,conversions,occupied_cells,largest_human_region,battles,cells_captured,secured_cells,human_control,zombie_control,neutral_control,emigrated"
+            )
+        {
+            error!(
+                "Stats export disabled, can't write to {:?}: {err}",
+                self.path
+            );
+            return;
+        }
+
+        app.insert_resource(StatsExport { writer, json })
+            .add_systems(
+                Update,
+                export_stats.in_set(Bookkeeping).after(sample_world_stats),
+            )
+            .add_systems(Last, flush_on_exit);
+    }
+}
+
+#[derive(Resource)]
+struct StatsExport {
+    writer: BufWriter<File>,
+    json: bool,
+}
+
+fn export_stats(stats: Res<WorldStats>, mut export: ResMut<StatsExport>) {
+    let Some(s) = stats.latest() else {
+        return;
+    };
+    let (human_control, zombie_control, neutral_control) = s.control_percent();
+    let line = if export.json {
+        format!(
+            "{{\"tick\":{},\"humans\":{},\"zombies\":{},\"conversions\":{},\"occupied_cells\":{},\"largest_human_region\":{},\"battles\":{},\"cells_captured\":{},\"secured_cells\":{},\"human_control\":{human_control:.1},\"zombie_control\":{zombie_control:.1},\"neutral_control\":{neutral_control:.1},\"emigrated\":{}}}",
+            s.tick,
+            s.humans,
+            s.zombies,
+            s.conversions,
+            s.occupied_cells,
+            s.largest_human_region,
+            s.battles,
+            s.cells_captured,
+            s.secured_cells,
+            s.emigrated
+        )
+    } else {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{human_control:.1},{zombie_control:.1},{neutral_control:.1},{}",
+            s.tick,
+            s.humans,
+            s.zombies,
+            s.conversions,
+            s.occupied_cells,
+            s.largest_human_region,
+            s.battles,
+            s.cells_captured,
+            s.secured_cells,
+            s.emigrated
+        )
+    };
+    if let Err(err) = writeln!(export.writer, "{line}") {
+        error!("Failed to export stats: {err}");
+    }
+}
+
+fn flush_on_exit(mut exit_events: EventReader<AppExit>, mut export: ResMut<StatsExport>) {
+    if exit_events.read().last().is_none() {
+        return;
+    }
+    if let Err(err) = export.writer.flush() {
+        error!("Failed to export stats: {err}");
+    }
+}
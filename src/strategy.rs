@@ -0,0 +1,66 @@
+use crate::config::MapConfig;
+use crate::tick::Bookkeeping;
+use crate::zombie_state::{Objective, Status, ZombieState};
+use bevy::prelude::*;
+
+const STRATEGY_EVERY_TICKS: u32 = 10;
+const REGION_SIZE: usize = 15; // cells per side of a region
+const DEFEND_MIN_RATIO: i64 = 2; // humans per zombie in a region worth holding
+const EVACUATE_MIN_RATIO: i64 = 2; // zombies per human in a region that is lost
+
+/// Slow strategic layer: every `STRATEGY_EVERY_TICKS` ticks it sizes up both sides in each
+/// region of the map and orders the humans there to defend or evacuate, see [`Objective`].
+pub struct StrategyPlugin;
+
+impl Plugin for StrategyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, assign_objectives.in_set(Bookkeeping));
+    }
+}
+
+fn assign_objectives(
+    mut ticks: Local<u32>,
+    map: Res<MapConfig>,
+    mut cells_q: Query<&mut ZombieState>,
+) {
+    *ticks += 1;
+    if !ticks.is_multiple_of(STRATEGY_EVERY_TICKS) {
+        return;
+    }
+
+    let regions_x = map.width.div_ceil(REGION_SIZE);
+    let regions_y = map.height.div_ceil(REGION_SIZE);
+    let region = |xy: IVec2| xy.y as usize / REGION_SIZE * regions_x + xy.x as usize / REGION_SIZE;
+
+    let mut forces = vec![(0i64, 0i64); regions_x * regions_y]; // humans, zombies
+    for state in cells_q.iter() {
+        let (humans, zombies) = &mut forces[region(state.xy)];
+        match state.status {
+            Status::Human | Status::Military => *humans += state.population as i64,
+            Status::Zombie => *zombies += state.population as i64,
+            Status::Empty => {}
+        }
+    }
+
+    let objectives: Vec<Objective> = forces
+        .iter()
+        .map(|&(humans, zombies)| {
+            if zombies == 0 {
+                Objective::None // nothing to worry about
+            } else if humans >= zombies * DEFEND_MIN_RATIO {
+                Objective::Defend
+            } else if zombies >= humans * EVACUATE_MIN_RATIO {
+                Objective::Evacuate
+            } else {
+                Objective::None
+            }
+        })
+        .collect();
+
+    for mut state in cells_q.iter_mut() {
+        let objective = objectives[region(state.xy)];
+        if state.objective != objective {
+            state.objective = objective;
+        }
+    }
+}
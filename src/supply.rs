@@ -0,0 +1,92 @@
+use crate::config::{MapConfig, SimulationConfig};
+use crate::tick::Bookkeeping;
+use crate::zombie_state::{ZombieState, direction_to_delta};
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// Traces supply lines after every tick: human cells connected through friendly cells
+/// to a settlement still standing where it was founded are supplied, the rest are cut off.
+/// Encircled human cells that give up fighting send a [`Surrender`] event.
+pub struct SupplyPlugin;
+
+impl Plugin for SupplyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Surrender>().add_systems(
+            Update,
+            (trace_supply_lines, detect_surrenders).in_set(Bookkeeping),
+        );
+    }
+}
+
+/// Humans in a cell surrounded by zombies with no supply line laid down their arms.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Surrender {
+    pub xy: IVec2,
+    pub population: i32,
+    pub settlement: u32, // lineage of the humans
+}
+
+fn trace_supply_lines(map: Res<MapConfig>, mut cells_q: Query<&mut ZombieState>) {
+    let index = |xy: IVec2| xy.y as usize * map.width + xy.x as usize;
+
+    let mut human = vec![false; map.width * map.height];
+    let mut faction = vec![0; map.width * map.height];
+    let mut supplied = vec![false; map.width * map.height];
+    let mut queue = VecDeque::new();
+    for state in cells_q.iter() {
+        // Army bases keep their soldiers and the humans around them supplied too
+        if !state.status.is_living() {
+            continue;
+        }
+        human[index(state.xy)] = true;
+        faction[index(state.xy)] = state.faction;
+        // Settlements are numbered after the cell they were founded in
+        if state.lineage as usize == index(state.xy) + 1 {
+            supplied[index(state.xy)] = true;
+            queue.push_back(state.xy);
+        }
+    }
+
+    while let Some(xy) = queue.pop_front() {
+        for direction in 0..8 {
+            let neighbor = xy + direction_to_delta(direction).unwrap();
+            if neighbor.x < 0
+                || neighbor.y < 0
+                || neighbor.x as usize >= map.width
+                || neighbor.y as usize >= map.height
+            {
+                continue;
+            }
+            let i = index(neighbor);
+            // Only along the cells of the same faction
+            if human[i] && !supplied[i] && faction[i] == faction[index(xy)] {
+                supplied[i] = true;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    for mut state in cells_q.iter_mut() {
+        let cell_supplied = supplied[index(state.xy)];
+        if state.supplied != cell_supplied {
+            state.supplied = cell_supplied;
+        }
+    }
+}
+
+fn detect_surrenders(
+    cells_q: Query<&ZombieState>,
+    config: Res<SimulationConfig>,
+    mut surrenders: EventWriter<Surrender>,
+) {
+    for state in cells_q.iter() {
+        // Sent once, when the cell starts surrendering on the next tick
+        if state.status.is_human() && state.encircled == config.surrender_after_ticks {
+            surrenders.send(Surrender {
+                xy: state.xy,
+                population: state.population,
+                settlement: state.lineage,
+            });
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use crate::tick::tick_due;
+use bevy::app::AppExit;
+use bevy::ecs::entity::Entities;
+use bevy::prelude::*;
+use bevy_life::LifeSystemSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+// How often a telemetry line is appended to the file
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+// Buckets the tick durations of the whole run are counted in, longer ones go in the last
+const HISTOGRAM_STEP: Duration = Duration::from_micros(50);
+const HISTOGRAM_BUCKETS: usize = 20_000; // up to a second
+
+/// Opt-in writer of long-run stability data (enabled with `--telemetry <file>`).
+///
+/// Every `SAMPLE_INTERVAL` a CSV line with tick duration percentiles, resident memory,
+/// entity and material counts is appended to the file; a summary is written next to it on exit.
+pub struct TelemetryPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        let file = match File::create(&self.path) {
+            Ok(file) => file,
+            Err(err) => {
+                error!("Telemetry disabled, can't create {:?}: {err}", self.path);
+                return;
+            }
+        };
+        let mut writer = BufWriter::new(file);
+        if let Err(err) = writeln!(
+            writer,
+            "elapsed_s,ticks,tick_p50_ms,tick_p90_ms,tick_p99_ms,tick_max_ms,rss_mb,entities,materials"
+        ) {
+            error!("Telemetry disabled, can't write to {:?}: {err}", self.path);
+            return;
+        }
+
+        app.insert_resource(Telemetry {
+            writer,
+            summary_path: self.path.with_extension("summary.txt"),
+            started: Instant::now(),
+            last_sample: Instant::now(),
+            tick_started: None,
+            ticks: 0,
+            window: Vec::new(),
+            all: Histogram::default(),
+            first: None,
+            last: None,
+            peak_rss: 0,
+        })
+        .add_systems(
+            Update,
+            (
+                tick_started.before(LifeSystemSet::CellUpdate),
+                tick_finished.after(LifeSystemSet::CellUpdate),
+            )
+                .run_if(tick_due),
+        )
+        .add_systems(Last, (sample, write_summary_on_exit).chain());
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Sample {
+    rss: Option<u64>,
+    entities: u32,
+    materials: usize,
+}
+
+#[derive(Resource)]
+struct Telemetry {
+    writer: BufWriter<File>,
+    summary_path: PathBuf,
+    started: Instant,
+    last_sample: Instant,
+    tick_started: Option<Instant>,
+    ticks: u64,
+    window: Vec<Duration>, // tick durations since the last sample
+    all: Histogram,        // tick durations of the whole run, for the summary
+    first: Option<Sample>,
+    last: Option<Sample>,
+    peak_rss: u64,
+}
+
+fn tick_started(mut telemetry: ResMut<Telemetry>) {
+    telemetry.tick_started = Some(Instant::now());
+}
+
+fn tick_finished(mut telemetry: ResMut<Telemetry>) {
+    if let Some(started) = telemetry.tick_started.take() {
+        let elapsed = started.elapsed();
+        telemetry.ticks += 1;
+        telemetry.window.push(elapsed);
+        telemetry.all.add(elapsed);
+    }
+}
+
+#[derive(Default)]
+struct Histogram {
+    counts: Vec<u64>, // per HISTOGRAM_STEP
+    total: u64,
+    max: Duration,
+}
+
+impl Histogram {
+    fn add(&mut self, duration: Duration) {
+        if self.counts.is_empty() {
+            self.counts = vec![0; HISTOGRAM_BUCKETS];
+        }
+        let bucket = (duration.as_nanos() / HISTOGRAM_STEP.as_nanos()) as usize;
+        self.counts[bucket.min(HISTOGRAM_BUCKETS - 1)] += 1;
+        self.total += 1;
+        self.max = self.max.max(duration);
+    }
+
+    /// Nearest-rank percentile in milliseconds, rounded up to its bucket, exact for the max.
+    fn percentile_ms(&self, p: f64) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        let rank = ((self.total as f64 * p).ceil() as u64).clamp(1, self.total);
+        let mut seen = 0;
+        for (bucket, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let upper = HISTOGRAM_STEP * (bucket as u32 + 1);
+                return upper.min(self.max).as_secs_f64() * 1000.0;
+            }
+        }
+        self.max.as_secs_f64() * 1000.0
+    }
+}
+
+fn sample(
+    mut telemetry: ResMut<Telemetry>,
+    entities: &Entities,
+    materials: Res<Assets<ColorMaterial>>,
+) {
+    if telemetry.last_sample.elapsed() < SAMPLE_INTERVAL {
+        return;
+    }
+    telemetry.last_sample = Instant::now();
+
+    let sample = Sample {
+        rss: resident_memory_bytes(),
+        entities: entities.len(),
+        materials: materials.len(),
+    };
+    telemetry.first.get_or_insert(sample);
+    telemetry.last = Some(sample);
+    telemetry.peak_rss = telemetry.peak_rss.max(sample.rss.unwrap_or(0));
+
+    let mut window = std::mem::take(&mut telemetry.window);
+    window.sort();
+    let line = format!(
+        "{:.0},{},{:.3},{:.3},{:.3},{:.3},{},{},{}",
+        telemetry.started.elapsed().as_secs_f64(),
+        telemetry.ticks,
+        percentile_ms(&window, 0.5),
+        percentile_ms(&window, 0.9),
+        percentile_ms(&window, 0.99),
+        percentile_ms(&window, 1.0),
+        sample
+            .rss
+            .map_or(String::new(), |rss| format!("{:.1}", mb(rss))),
+        sample.entities,
+        sample.materials,
+    );
+    if let Err(err) = writeln!(telemetry.writer, "{line}").and_then(|_| telemetry.writer.flush()) {
+        warn!("Failed to write telemetry: {err}");
+    }
+}
+
+fn write_summary_on_exit(mut exit_events: EventReader<AppExit>, mut telemetry: ResMut<Telemetry>) {
+    if exit_events.read().last().is_none() {
+        return;
+    }
+    let _ = telemetry.writer.flush();
+
+    let all = &telemetry.all;
+    let elapsed = telemetry.started.elapsed().as_secs();
+    let mut summary = format!(
+        "Telemetry summary\n\
+         duration: {}h {}m {}s\n\
+         ticks: {}\n\
+         tick duration ms: p50 {:.3}, p90 {:.3}, p99 {:.3}, max {:.3}\n\
+         peak resident memory MB: {:.1}\n",
+        elapsed / 3600,
+        elapsed / 60 % 60,
+        elapsed % 60,
+        telemetry.ticks,
+        all.percentile_ms(0.5),
+        all.percentile_ms(0.9),
+        all.percentile_ms(0.99),
+        all.percentile_ms(1.0),
+        mb(telemetry.peak_rss),
+    );
+    // Growth between the first and the last sample is what points at leaks
+    if let (Some(first), Some(last)) = (telemetry.first, telemetry.last) {
+        summary += &format!(
+            "resident memory MB: first sample {:.1}, last sample {:.1}\n\
+             entities: first sample {}, last sample {}\n\
+             color materials: first sample {}, last sample {}\n",
+            mb(first.rss.unwrap_or(0)),
+            mb(last.rss.unwrap_or(0)),
+            first.entities,
+            last.entities,
+            first.materials,
+            last.materials,
+        );
+    }
+
+    info!("{summary}");
+    if let Err(err) = std::fs::write(&telemetry.summary_path, summary) {
+        warn!("Failed to write telemetry summary: {err}");
+    }
+}
+
+/// Nearest-rank percentile of sorted durations, in milliseconds.
+fn percentile_ms(sorted: &[Duration], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((sorted.len() as f64 * p).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1].as_secs_f64() * 1000.0
+}
+
+fn mb(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+// Only available on Linux, other platforms leave the column empty
+fn resident_memory_bytes() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some(pages * 4096)
+}
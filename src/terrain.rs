@@ -1,8 +1,108 @@
+use crate::rng::scramble;
+use crate::zombie_state::direction_to_delta;
+use bevy::math::IVec2;
 use noise::{NoiseFn, Perlin};
+use serde::{Deserialize, Serialize};
+
+const RIVER_SOURCE_PERCENTILE: usize = 90; // rivers start on the highest tenth of the map
+const RIVER_SOURCE_ODDS: u64 = 50; // one in this many of those cells is a source
+const CITY_ODDS: u64 = 1500; // one in this many land cells is a city site, joined to the others by roads
+
+/// What the ground of a cell is, nobody can move onto water, rivers or walls.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum TerrainKind {
+    #[default]
+    Land,
+    Water,
+    River,
+    Road, // also bridges where it crosses a river
+    Wall, // set by hand in the config, see MapConfig::barriers
+}
+
+impl TerrainKind {
+    /// Kind of the ground at `altitude` (-1..1), water below `water_level`.
+    pub fn at(altitude: f32, water_level: f32) -> Self {
+        if altitude < water_level {
+            TerrainKind::Water
+        } else {
+            TerrainKind::Land
+        }
+    }
+
+    pub const ALL: [TerrainKind; 5] = [
+        TerrainKind::Land,
+        TerrainKind::Water,
+        TerrainKind::River,
+        TerrainKind::Road,
+        TerrainKind::Wall,
+    ];
+
+    pub fn is_impassable(&self) -> bool {
+        matches!(
+            self,
+            TerrainKind::Water | TerrainKind::River | TerrainKind::Wall
+        )
+    }
+}
+
+/// Climate of a cell, from its altitude, temperature and moisture.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Biome {
+    #[default]
+    Plains,
+    Forest,
+    Desert,
+    Tundra,
+    Swamp,
+}
+
+impl Biome {
+    pub const ALL: [Biome; 5] = [
+        Biome::Plains,
+        Biome::Forest,
+        Biome::Desert,
+        Biome::Tundra,
+        Biome::Swamp,
+    ];
+
+    /// Share of the food grown on plains that grows here.
+    pub fn food_yield_percent(&self) -> i32 {
+        match self {
+            Biome::Plains => 100,
+            Biome::Forest => 60,
+            Biome::Swamp => 40,
+            Biome::Tundra => 20,
+            Biome::Desert => 10,
+        }
+    }
+
+    /// Biome of ground with these terrain layer values (-1..1).
+    pub fn at(altitude: f32, temperature: f32, moisture: f32) -> Self {
+        if temperature < -0.25 {
+            Biome::Tundra
+        } else if moisture > 0.2 && altitude < 0.0 {
+            Biome::Swamp // wet lowlands
+        } else if temperature > 0.2 && moisture < -0.1 {
+            Biome::Desert
+        } else if moisture > 0.0 {
+            Biome::Forest
+        } else {
+            Biome::Plains
+        }
+    }
+}
+
+/// What [`TerrainGenerator::features`] lays over the terrain layers, `[y][x]` like them.
+pub struct Features {
+    pub kinds: Vec<Vec<TerrainKind>>,
+    pub cities: Vec<(usize, usize)>, // (x, y) of the city sites the roads join
+}
 
 pub struct TerrainGenerator {
+    seed: u64,
     altitude_perlin: Perlin,
     temperature_perlin: Perlin,
+    moisture_perlin: Perlin,
 }
 
 impl TerrainGenerator {
@@ -10,38 +110,121 @@ impl TerrainGenerator {
         // Derive two deterministic seeds from the original seed
         let altitude_seed = seed & 0xFFFF_FFFF; // Use the lower 32 bits
         let temperature_seed = (seed >> 32) & 0xFFFF_FFFF; // Use the upper 32 bits
+        let moisture_seed = seed.rotate_left(16) & 0xFFFF_FFFF; // Use the middle 32 bits
 
         // Create Perlin noise generators with derived seeds
         let altitude_perlin = Perlin::new(altitude_seed as u32);
         let temperature_perlin = Perlin::new(temperature_seed as u32);
+        let moisture_perlin = Perlin::new(moisture_seed as u32);
 
         TerrainGenerator {
+            seed,
             altitude_perlin,
             temperature_perlin,
+            moisture_perlin,
         }
     }
 
-    pub fn generate(&self, width: usize, height: usize, num_levels: i32, base_level: f64) -> Vec<Vec<Vec<f32>>> {
-        let mut terrain = vec![vec![vec![0.0; 2]; width]; height];
+    pub fn generate(
+        &self,
+        width: usize,
+        height: usize,
+        num_levels: i32,
+        base_level: f64,
+    ) -> Vec<Vec<Vec<f32>>> {
+        let mut terrain = vec![vec![vec![0.0; 3]; width]; height];
 
-        for y in 0..height {
-            for x in 0..width {
-                // Generate altitude and temperature using separate Perlin noise generators
+        for (y, row) in terrain.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                // Generate altitude, temperature and moisture using separate Perlin noise generators
                 let mut altitude = 0.0;
                 // Use multiple levels of detail for altitude
                 for level in 0..num_levels {
                     let scale = 1 << level; // Scale factor for each level
-                    altitude += self.altitude_perlin.get([x as f64 / (base_level / scale as f64), y as f64 / (base_level / scale as f64)]) as f32 / (num_levels as f32);
+                    altitude += self.altitude_perlin.get([
+                        x as f64 / (base_level / scale as f64),
+                        y as f64 / (base_level / scale as f64),
+                    ]) as f32
+                        / (num_levels as f32);
                 }
-                let temperature = self.temperature_perlin.get([x as f64 / 20.0, y as f64 / 20.0]) as f32;
+                let temperature =
+                    self.temperature_perlin
+                        .get([x as f64 / 20.0, y as f64 / 20.0]) as f32;
+                let moisture = self.moisture_perlin.get([x as f64 / 30.0, y as f64 / 30.0]) as f32;
 
-                terrain[y][x] = vec![altitude, temperature];
+                *cell = vec![altitude, temperature, moisture];
             }
         }
 
         terrain
     }
 
+    /// Post-processing pass over generated layers: water below `water_level`, rivers running from high ground
+    /// down to the lowest neighbor until they reach water or a hollow, and roads joining every city site to the nearest one placed before it.
+    pub fn features(&self, terrain: &[Vec<Vec<f32>>], water_level: f32) -> Features {
+        let (width, height) = (terrain[0].len(), terrain.len());
+        let altitude = |(x, y): (usize, usize)| terrain[y][x][0];
+        let roll = |(x, y): (usize, usize), salt: u64| {
+            scramble(self.seed ^ scramble((((y as u64) << 32) | x as u64) ^ salt))
+        };
+        let mut kinds: Vec<Vec<TerrainKind>> = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| TerrainKind::at(altitude((x, y)), water_level))
+                    .collect()
+            })
+            .collect();
+
+        let mut altitudes: Vec<f32> = terrain.iter().flatten().map(|layers| layers[0]).collect();
+        altitudes.sort_by(f32::total_cmp);
+        let source_altitude = altitudes[(altitudes.len() - 1) * RIVER_SOURCE_PERCENTILE / 100];
+        for y in 0..height {
+            for x in 0..width {
+                if altitude((x, y)) < source_altitude || roll((x, y), 1) % RIVER_SOURCE_ODDS != 0 {
+                    continue;
+                }
+                // Downhill until the river ends in water, a hollow, or another river
+                let mut cell = (x, y);
+                while kinds[cell.1][cell.0] == TerrainKind::Land {
+                    kinds[cell.1][cell.0] = TerrainKind::River;
+                    match neighbors(cell, width, height)
+                        .min_by(|a, b| altitude(*a).total_cmp(&altitude(*b)))
+                    {
+                        Some(lowest) if altitude(lowest) < altitude(cell) => cell = lowest,
+                        _ => break,
+                    }
+                }
+            }
+        }
+
+        let mut cities: Vec<(usize, usize)> = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                if kinds[y][x] != TerrainKind::Land || roll((x, y), 2) % CITY_ODDS != 0 {
+                    continue;
+                }
+                let distance = |(cx, cy): (usize, usize)| x.abs_diff(cx).max(y.abs_diff(cy));
+                if let Some(&nearest) = cities.iter().min_by_key(|city| distance(**city)) {
+                    // Straight (and diagonal) toward the other city, bridging rivers but not open water
+                    let mut cell = (x, y);
+                    while cell != nearest {
+                        cell = (
+                            step_toward(cell.0, nearest.0),
+                            step_toward(cell.1, nearest.1),
+                        );
+                        if kinds[cell.1][cell.0] != TerrainKind::Water {
+                            kinds[cell.1][cell.0] = TerrainKind::Road;
+                        }
+                    }
+                }
+                kinds[y][x] = TerrainKind::Road;
+                cities.push((x, y));
+            }
+        }
+
+        Features { kinds, cities }
+    }
+
     // Utility function to print a 2D visualization of the terrain
     pub fn print_map(terrain: &Vec<Vec<Vec<f32>>>, index: usize) {
         let ascii_gradient = [
@@ -54,7 +237,8 @@ impl TerrainGenerator {
                     // Normalize the value to a range of 0 to 1
                     let normalized = ((*value + 1.0) / 2.0).clamp(0.0, 1.0);
                     // Map the normalized value to an ASCII character
-                    let char_index = (normalized * (ascii_gradient.len() - 1) as f32).round() as usize;
+                    let char_index =
+                        (normalized * (ascii_gradient.len() - 1) as f32).round() as usize;
                     print!("{}", ascii_gradient[char_index]);
                 } else {
                     print!("?");
@@ -63,4 +247,67 @@ impl TerrainGenerator {
             println!(); // Newline after each row
         }
     }
-}
\ No newline at end of file
+}
+
+/// The up to 8 cells around `(x, y)` that are on the map.
+fn neighbors(
+    (x, y): (usize, usize),
+    width: usize,
+    height: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    (-1..=1)
+        .flat_map(move |dy| (-1..=1).map(move |dx| (x as i64 + dx, y as i64 + dy)))
+        .filter(move |&(nx, ny)| {
+            (nx, ny) != (x as i64, y as i64)
+                && nx >= 0
+                && ny >= 0
+                && nx < width as i64
+                && ny < height as i64
+        })
+        .map(|(nx, ny)| (nx as usize, ny as usize))
+}
+
+fn step_toward(from: usize, to: usize) -> usize {
+    match from.cmp(&to) {
+        std::cmp::Ordering::Less => from + 1,
+        std::cmp::Ordering::Greater => from - 1,
+        std::cmp::Ordering::Equal => from,
+    }
+}
+
+/// Passable cells whose removal would cut their neighborhood in two, `[y][x]` like the
+/// terrain `kinds` (with the barriers in).
+///
+/// Approximated locally: going around the 8 neighbors, the passable ones next to each other
+/// along the ring form passages. Two or more separate passages mean the cell is the only way
+/// between them, e.g. a gap in a wall or a strip of land between two lakes.
+pub fn find_chokepoints(kinds: &[Vec<TerrainKind>]) -> Vec<Vec<bool>> {
+    let (width, height) = (kinds[0].len() as i32, kinds.len() as i32);
+    let passable = |xy: IVec2| {
+        xy.x >= 0
+            && xy.y >= 0
+            && xy.x < width
+            && xy.y < height
+            && !kinds[xy.y as usize][xy.x as usize].is_impassable()
+    };
+
+    let mut chokepoints = vec![vec![false; width as usize]; height as usize];
+    for y in 0..height {
+        for x in 0..width {
+            let xy = IVec2::new(x, y);
+            if !passable(xy) {
+                continue;
+            }
+            // Ring of neighbors, clockwise from the north
+            let ring: Vec<bool> = (0..8)
+                .map(|direction| passable(xy + direction_to_delta(direction).unwrap()))
+                .collect();
+
+            // Count where a passage starts: an open neighbor not joined to the previous one
+            let passages = (0..8).filter(|&i| ring[i] && !ring[(i + 7) % 8]).count();
+            // A fully open ring has no passage start, and a single passage is a dead end
+            chokepoints[y as usize][x as usize] = passages >= 2;
+        }
+    }
+    chokepoints
+}
@@ -0,0 +1,197 @@
+use crate::Terrain;
+use crate::config::MapConfig;
+use crate::help::KeybindingsAppExt;
+use crate::loading::LoadingState;
+use crate::overlays::{map_image, map_sprite};
+use bevy::prelude::*;
+
+/// Turns the terrain layers into one-texel-per-cell images so they can be blended over the
+/// terrain, under the populations. Hillshaded relief is shown by default,
+/// `L` cycles through the layers.
+pub struct TerrainLayersPlugin;
+
+impl Plugin for TerrainLayersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_keybindings("Terrain", &[("L", "cycle the terrain layer shown")])
+            .init_resource::<ShownLayer>()
+            .init_resource::<HillshadeLight>()
+            .add_systems(OnEnter(LoadingState::BuildingViews), setup_terrain_layers)
+            .add_systems(
+                Update,
+                (update_hillshade, cycle_terrain_layer).run_if(in_state(LoadingState::Ready)),
+            );
+    }
+}
+
+/// Terrain layers as RGBA images, row 0 is the top (highest y) row of the map.
+#[derive(Resource)]
+pub struct TerrainImages {
+    pub altitude: Handle<Image>,
+    pub temperature: Handle<Image>,
+    pub moisture: Handle<Image>,
+    pub hillshade: Handle<Image>,
+}
+
+/// Light used for the hillshade, changing it (or the terrain) re-renders the relief.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct HillshadeLight {
+    pub azimuth: f32,      // degrees, counter-clockwise from east
+    pub elevation: f32,    // degrees above the horizon
+    pub exaggeration: f32, // vertical scale of the -1..1 altitude relative to a cell's width
+}
+
+impl Default for HillshadeLight {
+    fn default() -> Self {
+        HillshadeLight {
+            azimuth: 135.0, // from the north-west, as on most printed maps
+            elevation: 45.0,
+            exaggeration: 40.0,
+        }
+    }
+}
+
+/// Opacity of the relief over the terrain, it only shades the biomes.
+const HILLSHADE_ALPHA: f32 = 0.4;
+/// Opacity of the other layers, the biomes and water still show through.
+const LAYER_ALPHA: f32 = 0.7;
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+enum ShownLayer {
+    None,
+    #[default]
+    Hillshade,
+    Altitude,
+    Temperature,
+    Moisture,
+}
+
+#[derive(Component)]
+struct TerrainLayerView;
+
+fn setup_terrain_layers(
+    map: Res<MapConfig>,
+    terrain: Res<Terrain>,
+    light: Res<HillshadeLight>,
+    mut images: ResMut<Assets<Image>>,
+    mut commands: Commands,
+) {
+    // Altitude as grayscale, temperature from cold blue to warm red, moisture from dry tan to wet teal
+    let altitude = images.add(layer_image(&terrain, 0, |n| {
+        let v = (n * 255.0) as u8;
+        [v, v, v, 255]
+    }));
+    let temperature = images.add(layer_image(&terrain, 1, |n| {
+        [(50.0 + n * 205.0) as u8, 80, (255.0 - n * 205.0) as u8, 255]
+    }));
+    let moisture = images.add(layer_image(&terrain, 2, |n| {
+        [
+            (210.0 - n * 180.0) as u8,
+            (180.0 - n * 40.0) as u8,
+            (120.0 + n * 40.0) as u8,
+            255,
+        ]
+    }));
+    let hillshade = images.add(hillshade_image(&terrain, &light));
+
+    // Over the terrain, under the night tint
+    let (mut sprite, transform) = map_sprite(&map, hillshade.clone(), 1.05);
+    sprite.color = Color::WHITE.with_alpha(HILLSHADE_ALPHA);
+    commands.spawn((sprite, transform, TerrainLayerView));
+
+    commands.insert_resource(TerrainImages {
+        altitude,
+        temperature,
+        moisture,
+        hillshade,
+    });
+}
+
+fn update_hillshade(
+    terrain: Res<Terrain>,
+    light: Res<HillshadeLight>,
+    terrain_images: Res<TerrainImages>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !terrain.is_changed() && !light.is_changed() {
+        return;
+    }
+    if let Some(image) = images.get_mut(&terrain_images.hillshade) {
+        *image = hillshade_image(&terrain, &light);
+    }
+}
+
+/// Lambertian shading of the altitude surface, slopes from central differences.
+fn hillshade_image(terrain: &Terrain, light: &HillshadeLight) -> Image {
+    let (width, height) = (terrain.0[0].len(), terrain.0.len());
+    let altitude = |x: usize, y: usize| terrain.0[y][x][0] * light.exaggeration;
+
+    let (azimuth, elevation) = (light.azimuth.to_radians(), light.elevation.to_radians());
+    let to_light = Vec3::new(
+        elevation.cos() * azimuth.cos(),
+        elevation.cos() * azimuth.sin(),
+        elevation.sin(),
+    );
+
+    let mut shades = vec![vec![vec![0.0; 1]; width]; height];
+    for (y, row) in shades.iter_mut().enumerate() {
+        for (x, shade) in row.iter_mut().enumerate() {
+            let (left, right) = (x.saturating_sub(1), (x + 1).min(width - 1));
+            let (down, up) = (y.saturating_sub(1), (y + 1).min(height - 1));
+            let dz_dx = (altitude(right, y) - altitude(left, y)) / (right - left).max(1) as f32;
+            let dz_dy = (altitude(x, up) - altitude(x, down)) / (up - down).max(1) as f32;
+
+            let normal = Vec3::new(-dz_dx, -dz_dy, 1.0).normalize();
+            // Stored in -1..1 like the other layers
+            shade[0] = normal.dot(to_light).max(0.0) * 2.0 - 1.0;
+        }
+    }
+
+    layer_image(&Terrain(shades), 0, |n| {
+        [(n * 235.0) as u8, (n * 225.0) as u8, (n * 205.0) as u8, 255]
+    })
+}
+
+/// Builds an image of terrain `layer` with `color` applied to the value normalized to 0..1.
+fn layer_image(terrain: &Terrain, layer: usize, color: impl Fn(f32) -> [u8; 4]) -> Image {
+    map_image(terrain.0[0].len(), terrain.0.len(), |x, y| {
+        color(((terrain.0[y][x][layer] + 1.0) / 2.0).clamp(0.0, 1.0))
+    })
+}
+
+fn cycle_terrain_layer(
+    keys: Res<ButtonInput<KeyCode>>,
+    terrain_images: Res<TerrainImages>,
+    mut shown: ResMut<ShownLayer>,
+    mut view_q: Query<(&mut Sprite, &mut Visibility), With<TerrainLayerView>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyL) {
+        return;
+    }
+
+    *shown = match *shown {
+        ShownLayer::None => ShownLayer::Hillshade,
+        ShownLayer::Hillshade => ShownLayer::Altitude,
+        ShownLayer::Altitude => ShownLayer::Temperature,
+        ShownLayer::Temperature => ShownLayer::Moisture,
+        ShownLayer::Moisture => ShownLayer::None,
+    };
+    info!("Terrain layer: {:?}", *shown);
+
+    let (image, alpha) = match *shown {
+        ShownLayer::None => (None, 0.0),
+        ShownLayer::Hillshade => (Some(&terrain_images.hillshade), HILLSHADE_ALPHA),
+        ShownLayer::Altitude => (Some(&terrain_images.altitude), LAYER_ALPHA),
+        ShownLayer::Temperature => (Some(&terrain_images.temperature), LAYER_ALPHA),
+        ShownLayer::Moisture => (Some(&terrain_images.moisture), LAYER_ALPHA),
+    };
+    for (mut sprite, mut visibility) in view_q.iter_mut() {
+        match image {
+            None => *visibility = Visibility::Hidden,
+            Some(image) => {
+                sprite.image = image.clone();
+                sprite.color = Color::WHITE.with_alpha(alpha);
+                *visibility = Visibility::Visible;
+            }
+        }
+    }
+}
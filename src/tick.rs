@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use bevy_life::{LifeSystemSet, SimulationPause};
+use std::time::Duration;
+
+/// Paces the ticks and orders [`TickSet`] around the cell update, add it wherever the simulation
+/// runs. bevy_life updates the cells in `Update`, so the cell update, the hooks and our bookkeeping
+/// all run there, together on the frames a tick is due.
+pub struct TickSetsPlugin {
+    pub tick_time_step: Option<f64>, // seconds per tick, none ticks on every update
+}
+
+impl Plugin for TickSetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TickClock::new(self.tick_time_step))
+            .add_systems(First, advance_tick_clock)
+            .configure_sets(
+                Update,
+                (
+                    LifeSystemSet::CellUpdate.run_if(tick_due),
+                    TickSet::PreCombat
+                        .before(LifeSystemSet::CellUpdate)
+                        .run_if(tick_due),
+                    (TickSet::PostCombat, Bookkeeping, TickSet::PostDecision)
+                        .chain()
+                        .after(LifeSystemSet::CellUpdate),
+                    TickSet::PostCombat.run_if(tick_due),
+                    Bookkeeping.run_if(tick_due),
+                    TickSet::PostDecision.run_if(tick_due),
+                ),
+            );
+    }
+}
+
+/// Hooks for systems of other plugins (e.g. custom combat modifiers) into every tick, so they can
+/// read or change the cells without forking the rules.
+///
+/// A cell fights and decides its next move in one step of the cell update, the hooks are around it.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TickSet {
+    PreCombat,    // the moves decided on the last tick can still be changed
+    PostCombat,   // the fights are over, our own bookkeeping hasn't seen the outcome yet
+    PostDecision, // after the bookkeeping, the cells are as the next tick will find them
+}
+
+/// Our systems reading (and some adjusting) the outcome of a tick: supply lines, stats, reports.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bookkeeping;
+
+/// When the next tick is due. Nothing ticks while [`SimulationPause`] is in or the virtual time
+/// is paused (photo mode, an unfocused window), and never again once stopped.
+#[derive(Resource, Debug)]
+pub struct TickClock {
+    timer: Option<Timer>, // none ticks on every update
+    due: bool,
+    step: bool,    // one tick on the next frame, paused or not
+    stopped: bool, // the run is over
+}
+
+impl TickClock {
+    fn new(tick_time_step: Option<f64>) -> Self {
+        TickClock {
+            timer: tick_time_step
+                .map(|step| Timer::new(Duration::from_secs_f64(step), TimerMode::Repeating)),
+            due: false,
+            step: false,
+            stopped: false,
+        }
+    }
+
+    /// Whether this frame runs a tick.
+    pub fn is_due(&self) -> bool {
+        self.due
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn set_time_step(&mut self, seconds: f64) {
+        let step = Duration::from_secs_f64(seconds);
+        match &mut self.timer {
+            Some(timer) => timer.set_duration(step),
+            None => self.timer = Some(Timer::new(step, TimerMode::Repeating)),
+        }
+    }
+
+    /// Runs a single tick on the next frame, the cells only update if nothing pauses them then.
+    #[cfg(feature = "gui")]
+    pub fn step(&mut self) {
+        self.step = true;
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn is_stepping(&self) -> bool {
+        self.step
+    }
+
+    /// No more ticks for the rest of the run.
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+}
+
+fn advance_tick_clock(
+    time: Res<Time<Virtual>>,
+    pause: Option<Res<SimulationPause>>,
+    mut clock: ResMut<TickClock>,
+) {
+    let running = pause.is_none() && !time.is_paused();
+    // One tick per frame at most, a slow frame doesn't make up for the ones it missed
+    let on_time = match &mut clock.timer {
+        Some(timer) => running && timer.tick(time.delta()).just_finished(),
+        None => running,
+    };
+    clock.due = !clock.stopped && (clock.step || on_time);
+    clock.step = false;
+}
+
+/// Run condition of every tick system, see [`TickClock`].
+pub fn tick_due(clock: Res<TickClock>) -> bool {
+    clock.is_due()
+}
@@ -0,0 +1,95 @@
+use crate::photo::PhotoMode;
+use bevy::color::palettes::css::TOMATO;
+use bevy::prelude::*;
+
+const TOAST_SECONDS: f32 = 6.0;
+
+/// Shows [`Toast`]s, e.g. a failed save, in the bottom left corner for a few seconds.
+/// `startup` holds the ones from before the app was built, like a broken config file.
+pub struct ToastPlugin {
+    pub startup: Vec<String>,
+}
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Toast>()
+            .insert_resource(StartupToasts(self.startup.clone()))
+            .add_systems(Startup, setup_toasts)
+            .add_systems(Update, (show_toasts, expire_toasts).chain());
+    }
+}
+
+/// Something the user should know about, most often a recoverable error.
+#[derive(Event, Debug, Clone)]
+pub struct Toast(pub String);
+
+#[derive(Resource)]
+struct StartupToasts(Vec<String>);
+
+#[derive(Component)]
+struct ToastList;
+
+#[derive(Component)]
+struct ToastTimer(Timer);
+
+fn setup_toasts(
+    startup: Res<StartupToasts>,
+    mut toasts: EventWriter<Toast>,
+    mut commands: Commands,
+) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            left: Val::Px(8.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+        ToastList,
+    ));
+    for message in &startup.0 {
+        toasts.send(Toast(message.clone()));
+    }
+    commands.remove_resource::<StartupToasts>();
+}
+
+fn show_toasts(
+    mut toasts: EventReader<Toast>,
+    list_q: Query<Entity, With<ToastList>>,
+    mut commands: Commands,
+) {
+    let Ok(list) = list_q.get_single() else {
+        return;
+    };
+    for toast in toasts.read() {
+        commands.entity(list).with_child((
+            Text::new(toast.0.clone()),
+            TextFont::from_font_size(16.0),
+            TextColor(TOMATO.into()),
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            ToastTimer(Timer::from_seconds(TOAST_SECONDS, TimerMode::Once)),
+        ));
+    }
+}
+
+fn expire_toasts(
+    time: Res<Time<Real>>, // keeps counting while the simulation is paused
+    photo: Option<Res<PhotoMode>>,
+    mut list_q: Query<&mut Visibility, With<ToastList>>,
+    mut toasts_q: Query<(Entity, &mut ToastTimer)>,
+    mut commands: Commands,
+) {
+    for (toast, mut timer) in toasts_q.iter_mut() {
+        if timer.0.tick(time.delta()).finished() {
+            commands.entity(toast).despawn();
+        }
+    }
+    // Kept out of the photos
+    for mut visibility in list_q.iter_mut() {
+        visibility.set_if_neq(match photo {
+            Some(_) => Visibility::Hidden,
+            None => Visibility::Inherited,
+        });
+    }
+}
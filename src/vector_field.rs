@@ -0,0 +1,112 @@
+use crate::config::MapConfig;
+use crate::overlays::{Overlays, cell_to_world};
+use crate::tick::Bookkeeping;
+use crate::zombie_state::{Status, ZombieState, direction_to_delta};
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+const BLOCK_SIZE: usize = 4; // cells per side of an arrow's area
+const WINDOW: usize = 20; // ticks the movement is averaged over
+
+/// Overlay (V) of the net human and zombie movement per block, averaged over the last ticks.
+pub struct VectorFieldPlugin;
+
+impl Plugin for VectorFieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MovementHistory>()
+            .add_systems(Update, record_movement.in_set(Bookkeeping))
+            .add_systems(
+                Update,
+                draw_vector_field.run_if(|overlays: Res<Overlays>| overlays.vector_field),
+            );
+    }
+}
+
+/// Net (humans, zombies) movement per block, one entry per tick, oldest first.
+#[derive(Resource, Default)]
+struct MovementHistory(VecDeque<Vec<(Vec2, Vec2)>>);
+
+fn record_movement(
+    map: Res<MapConfig>,
+    cells_q: Query<&ZombieState>,
+    mut history: ResMut<MovementHistory>,
+) {
+    let (blocks_x, blocks_y) = block_counts(&map);
+    let mut blocks = vec![(Vec2::ZERO, Vec2::ZERO); blocks_x * blocks_y];
+    for state in cells_q.iter() {
+        let block = &mut blocks[block_index(blocks_x, state.xy)];
+        let moved: Vec2 = (0..8)
+            .map(|direction| {
+                direction_to_delta(direction).unwrap().as_vec2()
+                    * state.sent_toward(direction) as f32
+            })
+            .sum();
+        match state.status {
+            Status::Human | Status::Military => block.0 += moved,
+            Status::Zombie => block.1 += moved,
+            Status::Empty => {}
+        }
+    }
+
+    if history.0.len() == WINDOW {
+        history.0.pop_front();
+    }
+    history.0.push_back(blocks);
+}
+
+/// Blocks across and down the map.
+fn block_counts(map: &MapConfig) -> (usize, usize) {
+    (
+        map.width.div_ceil(BLOCK_SIZE),
+        map.height.div_ceil(BLOCK_SIZE),
+    )
+}
+
+fn block_index(blocks_x: usize, xy: IVec2) -> usize {
+    (xy.y as usize / BLOCK_SIZE) * blocks_x + xy.x as usize / BLOCK_SIZE
+}
+
+fn draw_vector_field(map: Res<MapConfig>, history: Res<MovementHistory>, mut gizmos: Gizmos) {
+    if history.0.is_empty() {
+        return;
+    }
+
+    let (blocks_x, blocks_y) = block_counts(&map);
+    let mut average = vec![(Vec2::ZERO, Vec2::ZERO); blocks_x * blocks_y];
+    for blocks in &history.0 {
+        for (sum, (humans, zombies)) in average.iter_mut().zip(blocks) {
+            sum.0 += *humans / history.0.len() as f32;
+            sum.1 += *zombies / history.0.len() as f32;
+        }
+    }
+
+    // The strongest flow spans a whole block, the rest are scaled relative to it
+    let max = average
+        .iter()
+        .map(|(humans, zombies)| humans.length().max(zombies.length()))
+        .fold(0.0, f32::max);
+    if max <= 0.0 {
+        return;
+    }
+
+    for (i, (humans, zombies)) in average.iter().enumerate() {
+        let (bx, by) = (i % blocks_x, i / blocks_x);
+        let center = Vec2::new(
+            (bx * BLOCK_SIZE) as f32 + (BLOCK_SIZE as f32 - 1.0) / 2.0,
+            (by * BLOCK_SIZE) as f32 + (BLOCK_SIZE as f32 - 1.0) / 2.0,
+        );
+        for (flow, color) in [
+            (*humans, Color::srgb(0.3, 0.5, 1.0)),
+            (*zombies, Color::srgb(0.2, 0.9, 0.2)),
+        ] {
+            let arrow = flow / max * BLOCK_SIZE as f32;
+            if arrow.length() > 0.25 {
+                gizmos.arrow_2d(
+                    cell_to_world(&map, center - arrow / 2.0),
+                    cell_to_world(&map, center + arrow / 2.0),
+                    color,
+                );
+            }
+        }
+    }
+}
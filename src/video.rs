@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, save_to_disk};
+use bevy::time::TimeUpdateStrategy;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Video-friendly playback (enabled with `--video <dir>`): every frame advances the clock
+/// by exactly `1 / fps` seconds however long it took to render, so the ticks always land on the
+/// same frames (with the default 0.1s tick, one every 6 frames at 60 fps, 10 ticks per second of
+/// video), and every frame is dumped to `dir` as a numbered PNG, ready to be stitched into a
+/// video. Frames are also held back to `fps` of real time, so the window plays at the video's pace.
+pub struct VideoPlugin {
+    pub dir: PathBuf,
+    pub fps: u32,
+}
+
+impl Plugin for VideoPlugin {
+    fn build(&self, app: &mut App) {
+        if let Err(err) = std::fs::create_dir_all(&self.dir) {
+            error!("Video frames disabled, can't create {:?}: {err}", self.dir);
+            return;
+        }
+
+        let frame_time = Duration::from_secs_f64(1.0 / self.fps as f64);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(frame_time))
+            .insert_resource(FrameDump {
+                dir: self.dir.clone(),
+                frame: 0,
+            })
+            .insert_resource(FrameCap(frame_time))
+            .add_systems(Last, (dump_frame, cap_frame_rate));
+    }
+}
+
+/// Real time a frame takes at least.
+#[derive(Resource)]
+struct FrameCap(Duration);
+
+#[derive(Resource)]
+struct FrameDump {
+    dir: PathBuf,
+    frame: u64,
+}
+
+fn dump_frame(mut dump: ResMut<FrameDump>, mut commands: Commands) {
+    dump.frame += 1;
+    let path = dump.dir.join(format!("frame-{:06}.png", dump.frame));
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path));
+}
+
+/// Sleeps out what's left of the frame time since the last frame ended.
+fn cap_frame_rate(cap: Res<FrameCap>, mut last: Local<Option<Instant>>) {
+    if let Some(elapsed) = last.map(|last| last.elapsed()) {
+        std::thread::sleep(cap.0.saturating_sub(elapsed));
+    }
+    *last = Some(Instant::now());
+}
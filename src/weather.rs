@@ -0,0 +1,155 @@
+use crate::config::{MapConfig, SimulationConfig};
+use crate::rng::SimRng;
+use crate::tick::Bookkeeping;
+use crate::zombie_state::{Weather, ZombieState, direction_to_delta};
+#[cfg(feature = "gui")]
+use crate::{
+    loading::LoadingState,
+    overlays::{map_image, map_sprite},
+};
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+const FRONT_CHANCE_PERCENT: u32 = 2; // chance of a new front on every tick
+const MAX_FRONTS: usize = 3;
+const FRONT_TICKS: u32 = 200;
+const FRONT_RADIUS: std::ops::Range<f32> = 6.0..15.0; // cells
+const FRONT_SPEED: f32 = 0.2; // cells per tick
+
+/// Fronts of rain, fog and snow drifting over the map with the wind, every cell under one gets
+/// its weather.
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WeatherFronts>()
+            .add_systems(Update, move_weather_fronts.in_set(Bookkeeping));
+    }
+}
+
+/// Draws the fronts as a translucent layer over the terrain.
+#[cfg(feature = "gui")]
+pub struct WeatherOverlayPlugin;
+
+#[cfg(feature = "gui")]
+impl Plugin for WeatherOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(LoadingState::BuildingViews), setup_weather_overlay)
+            .add_systems(
+                Update,
+                update_weather_overlay.run_if(in_state(LoadingState::Ready)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Front {
+    pub weather: Weather,
+    pub center: Vec2, // in cells
+    pub radius: f32,
+    pub velocity: Vec2, // cells per tick
+    pub ticks_left: u32,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct WeatherFronts(pub Vec<Front>);
+
+impl WeatherFronts {
+    /// Weather at `xy`, the newest front over it wins.
+    pub fn weather_at(&self, xy: IVec2) -> Weather {
+        self.0
+            .iter()
+            .rev()
+            .find(|front| front.center.distance(xy.as_vec2()) <= front.radius)
+            .map_or(Weather::Clear, |front| front.weather)
+    }
+}
+
+pub fn move_weather_fronts(
+    map: Res<MapConfig>,
+    rules: Res<SimulationConfig>,
+    mut rng: ResMut<SimRng>,
+    mut fronts: ResMut<WeatherFronts>,
+    mut cells_q: Query<&mut ZombieState>,
+) {
+    for front in fronts.0.iter_mut() {
+        front.center += front.velocity;
+        front.ticks_left -= 1;
+    }
+    fronts.0.retain(|front| front.ticks_left > 0);
+
+    if fronts.0.len() < MAX_FRONTS && rng.0.random_range(0..100) < FRONT_CHANCE_PERCENT {
+        let weather = match rng.0.random_range(0..3) {
+            0 => Weather::Rain,
+            1 => Weather::Fog,
+            _ => Weather::Snow,
+        };
+        // Blown along by the wind, drifting some random way when it's calm
+        let direction = match rules.wind.direction {
+            8 => rng.0.random_range(0..8),
+            direction => direction,
+        };
+        let heading = direction_to_delta(direction)
+            .unwrap_or_default()
+            .as_vec2()
+            .normalize_or_zero();
+        fronts.0.push(Front {
+            weather,
+            center: Vec2::new(
+                rng.0.random_range(0.0..map.width as f32),
+                rng.0.random_range(0.0..map.height as f32),
+            ),
+            radius: rng.0.random_range(FRONT_RADIUS),
+            velocity: heading * FRONT_SPEED,
+            ticks_left: FRONT_TICKS,
+        });
+    }
+
+    for mut state in cells_q.iter_mut() {
+        // Only touched where the weather changes, so the other cells aren't marked changed
+        let weather = fronts.weather_at(state.xy);
+        if state.weather != weather {
+            state.weather = weather;
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+#[derive(Resource)]
+struct WeatherOverlay(Handle<Image>);
+
+#[cfg(feature = "gui")]
+fn setup_weather_overlay(
+    map: Res<MapConfig>,
+    mut images: ResMut<Assets<Image>>,
+    mut commands: Commands,
+) {
+    let image = images.add(map_image(map.width, map.height, |_, _| [0, 0, 0, 0]));
+    // Over the night tint, under the populations
+    commands.spawn(map_sprite(&map, image.clone(), 1.15));
+    commands.insert_resource(WeatherOverlay(image));
+}
+
+#[cfg(feature = "gui")]
+fn update_weather_overlay(
+    map: Res<MapConfig>,
+    fronts: Res<WeatherFronts>,
+    overlay: Res<WeatherOverlay>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !fronts.is_changed() {
+        return;
+    }
+    let Some(image) = images.get_mut(&overlay.0) else {
+        return;
+    };
+    *image = map_image(map.width, map.height, |x, y| {
+        match fronts.weather_at(IVec2::new(x as i32, y as i32)) {
+            Weather::Clear => [0, 0, 0, 0],
+            Weather::Rain => [60, 90, 200, 90],
+            Weather::Fog => [200, 200, 200, 120],
+            Weather::Snow => [255, 255, 255, 140],
+        }
+    });
+}
@@ -1,43 +1,137 @@
+use crate::config::{Rules, SimulationConfig, ZombieIdle};
+use crate::error::{Error, Result};
+use crate::rng::scramble;
+use crate::terrain::{Biome, TerrainKind};
 use bevy::prelude::warn;
-use bevy::{audio::CpalSample, math::IVec2, prelude::Component};
+use bevy::{math::IVec2, prelude::Component};
 use bevy_life::CellState;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 
-#[derive(Debug, Clone, Default, Eq, PartialEq, Component)]
+/// Smell is kept in hundredths of what a unit of population gives off, so the weak gradients
+/// far from a source don't round away. Still integers, so runs replay exactly.
+pub const SMELL_SCALE: i32 = 100;
+
+/// Longest the bitten can take to turn, see SimulationConfig::incubation_ticks.
+pub const MAX_INCUBATION_TICKS: usize = 16;
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Component, Serialize, Deserialize)]
 pub enum Status {
     #[default]
     Empty,
     Zombie,
     Human,
+    Military, // soldiers of an army base, holding it and firing on the zombies next to it
 }
 
 impl Status {
     #[inline]
-    fn is_empty(&self) -> bool {
+    pub fn is_empty(&self) -> bool {
         self == &Self::Empty
     }
 
     #[inline]
-    fn is_human(&self) -> bool {
+    pub fn is_human(&self) -> bool {
         self == &Self::Human
     }
 
     #[inline]
-    fn is_zombie(&self) -> bool {
+    pub fn is_zombie(&self) -> bool {
         self == &Self::Zombie
     }
+
+    #[inline]
+    pub fn is_military(&self) -> bool {
+        self == &Self::Military
+    }
+
+    /// Humans of either kind, soldiers included.
+    #[inline]
+    pub fn is_living(&self) -> bool {
+        self.is_human() || self.is_military()
+    }
 }
 
-#[derive(Debug, Clone, Default, Eq, PartialEq, Component)]
+/// Regional order from the strategic layer, biasing what the humans in a cell do.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Objective {
+    #[default]
+    None,
+    Defend,   // hold the ground, only leave to attack
+    Evacuate, // fall back to safer cells, don't attack
+}
+
+/// Weather over a cell, brought and taken away by the passing weather fronts.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain, // washes the smell away
+    Fog,  // humans make out less of the zombie smell
+    Snow, // fewer of a group get going
+}
+
+/// Where a cell's population change came from during the last tick.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Ledger {
+    pub births: i32,
+    pub human_deaths: i32,  // killed in combat, including the ones that turned
+    pub zombie_deaths: i32, // destroyed in combat
+    pub conversions: i32,   // killed humans that rose as zombies
+    pub disease_deaths: i32,
+    pub decayed: i32,    // zombies rotted away in the heat
+    pub starved: i32,    // frontline humans lost while cut off from supply
+    pub stragglers: i32, // arriving from either side, lost crossing steep ground on the way here
+    pub emigrated: i32,  // walked off the open edge of the map
+    pub evacuated: i32,  // the emigrated, if they were humans
+    pub famine: i32,     // humans lost to hunger once the food ran out
+    pub cured: i32,      // zombies brought back by the humans next to them
+    pub recovered: i32,  // the cured, as the humans next to them took them in
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Component, Serialize, Deserialize)]
 pub struct ZombieState {
     pub xy: IVec2,        // (immutable, from terrain generation)
     pub altitude: i32,    // (immutable, from terrain generation)
-    pub temperature: i32, // (immutable, from terrain generation)
+    pub temperature: i32, // (from terrain generation, shifted by the seasons and cold snaps)
     pub status: Status,
     pub population: i32,
     pub direction: i8, // (Where they will either attack or reinforce on the next turn) (range 0-7), use own coordinate and neighbor coordinate to determine if incoming
-    pub smell_human: i32, // Human smell (0-100, 0 means no smell, 100 means very strong smell)
-    pub smell_zombie: i32, // Zombie smell (0-100, 0 means no smell, 100 means very strong smell)
+    pub outflow: [i32; 8], // Percent of the population sent toward each neighbor on the next turn, clockwise from the north, direction is the largest
+    pub smell_human: i32,  // Human smell, in SMELL_SCALE units (0 means no smell)
+    pub smell_zombie: i32, // Zombie smell, in SMELL_SCALE units (0 means no smell)
+    pub noise: i32,        // Combat noise, fast-decaying, blocked by mountains
+    pub panic: i32,        // Short-lived, spread from human cells that fell
+    pub panic_source: i8,  // Direction the panic came from (8 when it started here)
+    pub camp: bool,        // Overcrowded refugee camp, see SimulationConfig::cell_capacity
+    pub ledger: Ledger,    // Population change breakdown of the last tick
+    pub captures: u32,     // Times the cell changed hands between humans and zombies
+    pub lineage: u32, // Outbreak the zombies, or founding settlement the humans, here descend from (0 for none)
+    pub shamble: u8,  // Ticks frozen zombies have been waiting to make their next move
+    pub chokepoint: bool, // (immutable, from terrain generation) The only way between its neighbors
+    pub supplied: bool, // Connected through human cells to a settlement, traced after every tick
+    pub encircled: u8, // Ticks humans have been surrounded by zombies and cut off, see SimulationConfig::surrender_after_ticks
+    pub hiding: bool, // Survivors lying low, no smell and no attacks, see SimulationConfig::hide_max_population
+    pub intel_zombie: i32, // Zombie smell here as last seen by humans, see SimulationConfig::sight_max_climb
+    pub objective: Objective, // Set for the whole region by the strategic layer
+    pub night: bool, // Set for the whole map by the day/night cycle, see SimulationConfig::day_ticks
+    pub weather: Weather, // Set by the weather fronts passing over
+    pub moisture: i32, // (immutable, from terrain generation) Wet ground holds smell longer
+    pub corpses: i32, // The fallen lying here until they rot, they keep zombies going and draw more
+    pub secure_ticks: u8, // Ticks held with no zombie in sight or smell, patrols wear off once the humans leave
+    pub secured: bool,    // Held clear for long enough, see SimulationConfig::secure_after_ticks
+    pub terrain: TerrainKind, // (immutable, from terrain generation) Water and rivers can't be moved onto, roads speed movement up
+    pub biome: Biome,         // (immutable, from terrain generation)
+    pub open_edge: bool, // (immutable) On the border of a map with open edges, groups can walk off it
+    pub food: i32, // Stored here, grown every tick and eaten by the humans, see SimulationConfig::food_per_tick
+    pub calm_ticks: u8, // Ticks without fighting or groups coming or going, see SimulationConfig::birth_calm_ticks
+    pub fortification: i32, // Barricades human holders put up, percent added to their holder advantage
+    pub faction: u8, // Of the humans here, 0 for none. Factions don't join each other, they fight for the cell
+    pub variants: [i32; 3], // Percent of the zombies here that are runners, tanks and screamers, the rest are walkers
+    pub incubating: [i32; MAX_INCUBATION_TICKS], // Of the human population, the bitten by ticks left until they turn, see SimulationConfig::incubation_ticks
+    pub cured: bool, // Set for the whole map once the cure is researched, see SimulationConfig::cure_research_needed
+    #[serde(skip)]
+    pub rules: Rules, // The config the cell is updated with, the same for the whole map
 }
 
 impl CellState for ZombieState {
@@ -47,21 +141,138 @@ impl CellState for ZombieState {
 
         // Apply attack or reinforce from neighbors first, then update population, finally update intentions
         let neighbors: Vec<&Self> = neighbor_cells.collect();
+        let around: Vec<Neighbor> = self.around(&neighbors).collect();
+        let config: &SimulationConfig = &self.rules;
         // println!("neighbors: {neighbors:?}");
 
         // Next, look at the Direction value of all neighbors to see if any are sending zombies/humans our way.
         let mut incoming_humans = 0;
         let mut incoming_zombies = 0;
-        for neighbor in &neighbors {
-            // Check neighbor's direction to see if what they are sending is coming our way
-            // Find the DIRECTION_DELTA that matches the difference between our coordinates and the neighbor's coordinates
-            let delta = self.xy - neighbor.xy;
-            if neighbor.direction == delta_to_direction(delta).unwrap() {
+        // Whoever didn't set off keeps the cell
+        let stayed = self.population - self.moving_population();
+        // Once there's a cure, every human cell brings back some of the zombies that stayed next to
+        // it before they fight, both sides counting them from the cells as they were
+        let cure_share = |zombies: &Self| {
+            (zombies.population - zombies.moving_population()) * config.cure_percent / 100
+        };
+        let (cured, recovered) = match &self.status {
+            Status::Zombie if self.cured => {
+                let humans_around =
+                    around.iter().filter(|n| n.state.status.is_human()).count() as i32;
+                (cure_share(self) * humans_around, 0)
+            }
+            Status::Human if self.cured => {
+                let zombies_around = around.iter().filter(|n| n.state.status.is_zombie());
+                (0, zombies_around.map(|n| cure_share(n.state)).sum())
+            }
+            _ => (0, 0),
+        };
+        let stayed = stayed - cured;
+        // The largest group of each side arriving (or staying) passes its lineage on
+        let staying = |status: Status| {
+            if self.status == status && stayed > 0 {
+                (stayed, self.lineage)
+            } else {
+                (0, 0)
+            }
+        };
+        let mut dominant_zombies = staying(Status::Zombie);
+        // Zombies of every variant in the horde fighting here, x100, and the horde's size
+        let (mut horde, mut horde_size) = if self.status.is_zombie() {
+            (self.variants.map(|share| share * stayed), stayed)
+        } else {
+            ([0; 3], 0)
+        };
+        let mut dominant_humans = staying(Status::Human);
+        // Whoever holds the cell (and didn't all leave it) defends it from higher ground against attackers below
+        let defenders = if stayed > 0 {
+            self.status.clone()
+        } else {
+            Status::Empty
+        };
+        // Humans only join their own faction, the holders' or else the biggest group's arriving
+        let faction = if defenders.is_living() {
+            self.faction
+        } else {
+            around
+                .iter()
+                .filter(|n| n.state.status.is_human() && n.sending_here() > 0)
+                .max_by_key(|n| n.sending_here())
+                .map_or(0, |n| n.state.faction)
+        };
+        // The infected among the humans fighting here, they move with their group
+        let mut infected = if self.status.is_human() {
+            self.incubating
+                .map(|bitten| bitten * stayed / self.population.max(1))
+        } else {
+            [0; MAX_INCUBATION_TICKS]
+        };
+        let mut rivals = 0;
+        let mut rival = (0, 0, 0); // the biggest group of the others: sent, faction, lineage
+        let attack_strength = |attacker: &Neighbor, arrived: i32| {
+            let climb = (-attacker.altitude_delta).max(0);
+            let bonus = (climb * config.high_ground_bonus_percent)
+                .min(config.high_ground_max_bonus_percent);
+            arrived * 100 / (100 + bonus)
+        };
+        // Steep crossings cost a group some of its members on the way, zombies climb worse still
+        let arriving = |neighbor: &Neighbor| {
+            let (mover, climb) = (neighbor.state, (-neighbor.slope()).round() as i32);
+            let mut loss =
+                (climb.abs() - config.crossing_free_step).max(0) * config.crossing_loss_percent;
+            if mover.status.is_zombie() {
+                loss += climb.max(0) * config.zombie_climb_loss_percent;
+            }
+            if self.terrain == TerrainKind::Road && mover.terrain == TerrainKind::Road {
+                loss = loss * 100 / config.road_throughput_percent;
+            }
+            neighbor.sending_here() * (100 - loss.min(config.crossing_max_loss_percent)) / 100
+        };
+        let mut stragglers = 0;
+        for around_neighbor in &around {
+            // Check neighbor's outflow to see if some of what they are sending is coming our way
+            let sent = around_neighbor.sending_here();
+            if sent > 0 {
                 // If the neighbor is sending something our way, increment the appropriate counter
+                let neighbor = around_neighbor.state;
+                let arrived = arriving(around_neighbor);
+                stragglers += sent - arrived;
                 if neighbor.status.is_zombie() {
-                    incoming_zombies += neighbor.population;
+                    // The dark drives them on
+                    let arrived = if self.night {
+                        arrived * (100 + config.night_attack_bonus_percent) / 100
+                    } else {
+                        arrived
+                    };
+                    incoming_zombies += if defenders.is_living() {
+                        attack_strength(around_neighbor, arrived)
+                    } else {
+                        arrived
+                    };
+                    if sent > dominant_zombies.0 {
+                        dominant_zombies = (sent, neighbor.lineage);
+                    }
+                    for (members, share) in horde.iter_mut().zip(neighbor.variants) {
+                        *members += arrived * share;
+                    }
+                    horde_size += arrived;
+                } else if neighbor.status.is_human() && neighbor.faction != faction {
+                    rivals += arrived;
+                    if sent > rival.0 {
+                        rival = (sent, neighbor.faction, neighbor.lineage);
+                    }
                 } else if neighbor.status.is_human() {
-                    incoming_humans += neighbor.population;
+                    incoming_humans += if defenders.is_zombie() {
+                        attack_strength(around_neighbor, arrived)
+                    } else {
+                        arrived
+                    };
+                    if sent > dominant_humans.0 {
+                        dominant_humans = (sent, neighbor.lineage);
+                    }
+                    for (bitten, theirs) in infected.iter_mut().zip(neighbor.incubating) {
+                        *bitten += arrived * theirs / neighbor.population.max(1);
+                    }
                 }
             }
         }
@@ -72,26 +283,52 @@ impl CellState for ZombieState {
         // Now, update our own state based on incoming zombies and humans
         // Count how many zombies and humans we have (including ourselves). Give advantage to whichever holds this cell.
         let total_humans = incoming_humans
-            + if self.status.is_human() && self.direction == 8 {
-                self.population
+            + recovered
+            + if self.status.is_living() {
+                stayed
             } else {
                 0 // Our own population only counts if they didn't move away on the last turn!
             };
 
-        let total_zombies = incoming_zombies
-            + if self.status.is_zombie() && self.direction == 8 {
-                self.population
-            } else {
-                0
-            };
+        let variants = horde.map(|members| members / horde_size.max(1));
+        // Tanks take more to bring down, the horde fights with their weight
+        let weight = 100 + variants[1] * (config.tank_weight - 1);
+        let total_zombies =
+            (incoming_zombies + if self.status.is_zombie() { stayed } else { 0 }) * weight / 100;
 
         // println!("total_humans: {total_humans}");
         // println!("total_zombies: {total_zombies}");
 
         let mut new_state = self.clone();
+        // Whoever heads off an open edge has no neighbor to arrive at, and is gone
+        let emigrated = if self.open_edge {
+            (0..8)
+                .filter(|&direction| self.toward(&around, direction).is_none())
+                .map(|direction| self.sent_toward(direction))
+                .sum()
+        } else {
+            0
+        };
+        let mut ledger = Ledger {
+            stragglers,
+            emigrated,
+            cured,
+            recovered,
+            evacuated: if self.status.is_human() { emigrated } else { 0 },
+            ..Ledger::default()
+        };
 
         let humans_cmp_zombies = total_humans.cmp(&total_zombies);
 
+        // Humans surrounded with no way out give up fighting after a while
+        let encircled = self.status.is_human()
+            && !self.supplied
+            && neighbors.iter().all(|n| n.status.is_zombie());
+        let surrendering = encircled && self.encircled >= config.surrender_after_ticks;
+
+        // Both sides present means shots are fired, the noise scales with the smaller side
+        let mut battle_noise = total_humans.min(total_zombies) * config.noise_per_casualty;
+
         // Fight!
         match new_state.status {
             Status::Empty => {
@@ -100,14 +337,20 @@ impl CellState for ZombieState {
                     Ordering::Greater => {
                         new_state.status = Status::Human;
                         new_state.population = total_humans - total_zombies;
+                        ledger.human_deaths = total_zombies;
+                        ledger.zombie_deaths = total_zombies;
                     }
                     Ordering::Less => {
                         new_state.status = Status::Zombie;
                         new_state.population = total_zombies - total_humans;
+                        ledger.human_deaths = total_humans;
+                        ledger.zombie_deaths = total_humans;
                     }
                     Ordering::Equal => {
                         new_state.status = Status::Empty;
                         new_state.population = 0;
+                        ledger.human_deaths = total_humans;
+                        ledger.zombie_deaths = total_zombies;
                     }
                 }
             }
@@ -117,42 +360,231 @@ impl CellState for ZombieState {
                     Ordering::Greater => {
                         new_state.status = Status::Human;
                         new_state.population = total_humans - total_zombies;
+                        ledger.human_deaths = total_zombies;
+                        ledger.zombie_deaths = total_zombies;
                     }
                     Ordering::Less => {
-                        // Add 1/3 of humans to zombies to simulate the zombie infection spread
-                        new_state.population = total_zombies - total_humans + total_humans / 3;
+                        // Add some of the humans to zombies (1/3 by default) to simulate the zombie infection spread
+                        // The cured don't rise
+                        let turned = if self.cured {
+                            0
+                        } else {
+                            total_humans / config.conversion_divisor
+                        };
+                        new_state.population = total_zombies - total_humans + turned;
+                        ledger.human_deaths = total_humans;
+                        ledger.zombie_deaths = total_humans;
+                        ledger.conversions = turned;
                     }
                     Ordering::Equal => {
                         new_state.status = Status::Empty;
                         new_state.population = 0;
+                        ledger.human_deaths = total_humans;
+                        ledger.zombie_deaths = total_zombies;
                     }
                 }
             }
-            Status::Human => {
+            Status::Human | Status::Military => {
                 // Check if humans can hold the cell
                 // Human's have holder's advantage of 1 to 3, i.e., one human can take out 1 zombie.
-                match total_humans.cmp(&(total_zombies / 3)) {
+                // Crowded camps can barely organize a defense, neither can the cut off.
+                // Surrendering humans don't fight at all, and more of them rise.
+                let advantage = match (surrendering, self.camp, self.supplied) {
+                    (true, _, _) => 1,
+                    (false, true, _) => config.camp_holder_advantage,
+                    (false, false, false) => config.cut_off_holder_advantage,
+                    (false, false, true) => config.holder_advantage,
+                };
+                // Barricades put up while holding still, and daylight to shoot by, make every defender count for more
+                let advantage = if surrendering {
+                    advantage
+                } else {
+                    let daylight = if self.night {
+                        0
+                    } else {
+                        config.day_holder_bonus_percent
+                    };
+                    (advantage * (100 + self.fortification + daylight) / 100).max(1)
+                };
+                // Soldiers are trained and armed for it, the humans arriving join them
+                let advantage = if self.status.is_military() {
+                    advantage * config.military_combat_multiplier
+                } else {
+                    advantage
+                };
+                let turned = if self.cured {
+                    0
+                } else if surrendering {
+                    total_humans * config.surrender_turned_percent / 100
+                } else {
+                    total_humans / config.conversion_divisor
+                };
+                match total_humans.cmp(&(total_zombies / advantage)) {
                     Ordering::Greater => {
-                        new_state.population = total_humans - total_zombies / 3;
+                        new_state.population = total_humans - total_zombies / advantage;
+                        ledger.human_deaths = total_zombies / advantage;
+                        ledger.zombie_deaths = total_zombies;
                         // TODO "turned humans during combat"
                     }
                     Ordering::Less => {
                         new_state.status = Status::Zombie;
-                        new_state.population = total_zombies - total_humans * 3 + total_humans / 3;
+                        new_state.population = total_zombies - total_humans * advantage + turned;
+                        ledger.human_deaths = total_humans;
+                        ledger.zombie_deaths = total_humans * advantage;
+                        ledger.conversions = turned;
                     }
                     Ordering::Equal => {
                         new_state.status = Status::Empty;
                         new_state.population = 0; // Well, there should actually be some turned humans left after this fight
+                        ledger.human_deaths = total_humans;
+                        ledger.zombie_deaths = total_zombies;
                     }
                 }
             }
         }
 
+        // Back from the horde's weight to heads, the turned humans are walkers
+        ledger.zombie_deaths = ledger.zombie_deaths * 100 / weight;
+        if new_state.status.is_zombie() {
+            let horde_left = (new_state.population - ledger.conversions) * 100 / weight;
+            new_state.population = horde_left + ledger.conversions;
+            new_state.variants =
+                variants.map(|share| share * horde_left / new_state.population.max(1));
+        }
+
+        // Some of the humans who fought them off and lived were bitten, they join the infected
+        // once those whose time ran out have turned
+        let (bitten, infected) = if new_state.status.is_human() && !self.cured {
+            (
+                ledger.human_deaths * config.bitten_percent / 100,
+                infected.map(|bitten| bitten * new_state.population / total_humans.max(1)),
+            )
+        } else {
+            (0, [0; MAX_INCUBATION_TICKS])
+        };
+        new_state.incubating = infected;
+        keep_infected(&mut new_state.incubating, new_state.population);
+
+        // The other factions fight whoever is left for the cell
+        let rivals_took = rivals > new_state.population;
+        if rivals > 0 {
+            let fallen = rivals.min(new_state.population);
+            ledger.human_deaths += fallen; // of the rivals, as many as they take down
+            if new_state.status.is_zombie() {
+                ledger.zombie_deaths += fallen;
+            } else {
+                ledger.human_deaths += fallen;
+            }
+            battle_noise += fallen * config.noise_per_casualty;
+            if rivals_took {
+                new_state.status = Status::Human;
+                new_state.population = rivals - fallen;
+                new_state.incubating = [0; MAX_INCUBATION_TICKS];
+            } else {
+                new_state.population -= rivals;
+                keep_infected(&mut new_state.incubating, new_state.population);
+            }
+        }
+
+        // The infected whose time ran out turn, and the others fight them where they stand
+        if new_state.status.is_human() {
+            let rising = new_state.incubating[0];
+            new_state.incubating.rotate_left(1);
+            new_state.incubating[MAX_INCUBATION_TICKS - 1] = 0;
+            new_state.incubating[config.incubation_ticks as usize - 1] += bitten;
+            if rising > 0 {
+                // The risen are no longer among the humans, then as many of them as there are
+                // humans left fall on each side
+                new_state.population -= rising;
+                ledger.conversions += rising;
+                let fallen = rising.min(new_state.population);
+                ledger.human_deaths += fallen;
+                ledger.zombie_deaths += fallen;
+                battle_noise += fallen * config.noise_per_casualty;
+                if rising > new_state.population {
+                    new_state.status = Status::Zombie;
+                    new_state.population = rising - fallen;
+                    new_state.incubating = [0; MAX_INCUBATION_TICKS];
+                } else {
+                    new_state.population -= fallen;
+                }
+            }
+            keep_infected(&mut new_state.incubating, new_state.population);
+        }
+
+        // Humans holding still keep building barricades, they are lost to whoever takes the cell
+        new_state.fortification =
+            if self.status.is_human() && new_state.status.is_human() && !rivals_took {
+                if self.direction == 8 {
+                    let fortify = if self.night {
+                        config.fortify_per_tick
+                    } else {
+                        config.fortify_per_tick * (100 + config.day_fortify_bonus_percent) / 100
+                    };
+                    (self.fortification + fortify).min(config.fortification_max)
+                } else {
+                    self.fortification
+                }
+            } else {
+                0
+            };
+
+        // Soldiers next door fire on the zombies without leaving their base, better by day
+        let fire_percent = if self.night {
+            config.military_fire_percent
+        } else {
+            config.military_fire_percent * (100 + config.day_fire_bonus_percent) / 100
+        };
+        let fire: i32 = around
+            .iter()
+            .filter(|n| n.state.status.is_military())
+            .map(|n| n.state.population * fire_percent / 100)
+            .sum();
+        if new_state.status.is_zombie() && fire > 0 {
+            let shot = fire.min(new_state.population);
+            new_state.population -= shot;
+            ledger.zombie_deaths += shot;
+            battle_noise += shot * config.noise_per_casualty;
+        }
+
+        // Zombies rot in the heat, slower where there's plenty to feed on
+        if new_state.status.is_zombie() && self.temperature > config.heat_temperature {
+            let decay_percent = config.heat_decay_percent * 100
+                / (100 + self.corpses * config.corpse_sustain_percent);
+            ledger.decayed = (new_state.population * decay_percent / 100)
+                .max(1)
+                .min(new_state.population);
+            new_state.population -= ledger.decayed;
+        }
+
         // Check if population is zero, if so set its state to empty (just double-checking)
         if new_state.population == 0 {
             new_state.status = Status::Empty;
         }
 
+        new_state.lineage = match new_state.status {
+            Status::Zombie => dominant_zombies.1,
+            Status::Human if rivals_took => rival.2,
+            Status::Human => dominant_humans.1,
+            Status::Military => self.lineage, // of the base, whoever joins
+            Status::Empty => 0,
+        };
+        new_state.faction = match new_state.status {
+            Status::Human if rivals_took => rival.1,
+            Status::Human | Status::Military => faction,
+            Status::Zombie | Status::Empty => 0,
+        };
+        if !new_state.status.is_zombie() {
+            new_state.variants = [0; 3];
+        }
+
+        if !self.status.is_empty()
+            && !new_state.status.is_empty()
+            && self.status != new_state.status
+        {
+            new_state.captures += 1;
+        }
+
         if new_state.population < 0 {
             warn!(
                 "Cell's population is negative!\n Cell: {new_state:?}\n Neighbors: {neighbors:?}"
@@ -161,38 +593,232 @@ impl CellState for ZombieState {
 
         // println!("Battle ended, new_state: {new_state:?}");
 
+        // Ticks without fighting or anyone coming or going, families only grow once things settle
+        let calm = ledger.human_deaths == 0
+            && ledger.zombie_deaths == 0
+            && self.direction == 8
+            && incoming_humans == 0
+            && incoming_zombies == 0;
+        new_state.calm_ticks = if calm {
+            self.calm_ticks.saturating_add(1)
+        } else {
+            0
+        };
+        if new_state.status.is_human()
+            && new_state.calm_ticks >= config.birth_calm_ticks
+            && self.food >= config.birth_min_food
+        {
+            let before = new_state.population;
+            // Secured ground is where families settle down
+            let birth_rate = if self.secured {
+                config.birth_rate * (100 + config.secured_birth_bonus_percent) as f32 / 100.0
+            } else {
+                config.birth_rate
+            };
+            new_state.population = (new_state.population as f32 * (1.0 + birth_rate)) as i32; // Simulate birth rate
+            ledger.births = new_state.population - before;
+            // println!("Human population grew: {}", new_state.population);
+        }
+
+        // A camp forms when a flood of refugees overcrowds the cell and lasts until they disperse
+        new_state.camp = new_state.status.is_human()
+            && new_state.population > config.cell_capacity
+            && (self.camp || incoming_humans >= config.camp_min_inflow);
+        if new_state.camp {
+            ledger.disease_deaths = new_state.population * config.camp_disease_percent / 100;
+            new_state.population -= ledger.disease_deaths;
+        }
+
+        // A cut off frontline runs out of everything
+        let frontline = neighbors.iter().any(|n| n.status.is_zombie());
+        if self.status.is_human() && new_state.status.is_human() && !self.supplied && frontline {
+            ledger.starved = (new_state.population * config.cut_off_attrition_percent / 100)
+                .max(1)
+                .min(new_state.population);
+            new_state.population -= ledger.starved;
+            if new_state.population == 0 {
+                new_state.status = Status::Empty;
+                new_state.lineage = 0;
+            }
+        }
+        // The land grows food in the warmth, whoever can't be fed goes hungry
+        let grown = config.food_per_tick * self.biome.food_yield_percent() / 100
+            * (100 + self.temperature.clamp(-100, 100))
+            / 100;
+        let mut food = (self.food + grown).min(config.food_capacity);
         if new_state.status.is_human() {
-            new_state.population = new_state.population.mul_amp(1.01); // Simulate birth rate, 1%
-                                                                       // println!("Human population grew: {}", new_state.population);
+            let needed =
+                (new_state.population + config.people_per_food - 1) / config.people_per_food;
+            let eaten = needed.min(food);
+            food -= eaten;
+            let unfed = ((needed - eaten) * config.people_per_food).min(new_state.population);
+            if unfed > 0 {
+                ledger.famine = (unfed * config.famine_deaths_percent / 100)
+                    .max(1)
+                    .min(new_state.population);
+                new_state.population -= ledger.famine;
+                if new_state.population == 0 {
+                    new_state.status = Status::Empty;
+                    new_state.lineage = 0;
+                }
+            }
         }
+        new_state.food = food;
+        let hungry = ledger.famine > 0;
+
+        // The fallen stay behind until they rot away, the turned walk off
+        let fallen = ledger.human_deaths - ledger.conversions
+            + ledger.zombie_deaths
+            + ledger.disease_deaths
+            + ledger.starved
+            + ledger.famine;
+        let rotted = (self.corpses * config.corpse_rot_percent / 100)
+            .max(1)
+            .min(self.corpses);
+        new_state.corpses = self.corpses - rotted + fallen;
+        new_state.ledger = ledger;
+
+        // Ground held clear of zombies gets secured, and slowly stops being so once left alone
+        let zombies_around = new_state.status.is_zombie()
+            || self.smell_zombie > 0
+            || neighbors.iter().any(|n| n.status.is_zombie());
+        new_state.secure_ticks = if zombies_around {
+            0
+        } else if new_state.status.is_human() {
+            self.secure_ticks
+                .saturating_add(1)
+                .min(config.secure_after_ticks)
+        } else {
+            self.secure_ticks.saturating_sub(1)
+        };
+        new_state.secured = new_state.secure_ticks >= config.secure_after_ticks
+            || (self.secured && new_state.secure_ticks > 0);
+        new_state.encircled = if encircled && new_state.status.is_human() {
+            self.encircled.saturating_add(1)
+        } else {
+            0
+        };
 
-        // Update smell and noise. Set to average of neighbors, then add 1 for each population (human or zombie) in the cell.
-        new_state.smell_human = neighbors.iter().map(|n| n.smell_human).sum::<i32>()
-            / neighbors.len() as i32
-            + if self.status.is_human() {
-                self.population
+        // Update smell and noise. Spread some of it between neighbors and let it fade, then add 1 for each population (human or zombie) in the cell.
+        let diffuse = |smell: fn(&Self) -> i32| self.diffused(&around, smell, config);
+        let smell_max = config.smell_max * SMELL_SCALE;
+        new_state.smell_human = (diffuse(|n| n.smell_human)
+            + if self.status.is_living() && !self.hiding {
+                self.population * config.smell_per_population * SMELL_SCALE
             } else {
                 0
-            };
-        new_state.smell_zombie = neighbors.iter().map(|n| n.smell_zombie).sum::<i32>()
-            / neighbors.len() as i32
+            }
+            + self.corpses * config.corpse_smell_percent * SMELL_SCALE / 100)
+            .min(smell_max);
+        new_state.smell_zombie = (diffuse(|n| n.smell_zombie)
             + if self.status.is_zombie() {
-                self.population
+                // Screamers give off more than the walkers
+                let screaming =
+                    100 + self.variants[2] * (config.screamer_smell_percent - 100) / 100;
+                self.population * config.smell_per_population * SMELL_SCALE * screaming / 100
             } else {
                 0
-            };
+            })
+        .min(smell_max);
+
+        // Noise doesn't average out like smell, the loudest neighbor is heard over terrain
+        let heard = around
+            .iter()
+            .map(|n| {
+                let climb = (-n.altitude_delta).max(0);
+                let carry = (config.noise_carry_percent
+                    - climb * config.noise_climb_penalty_percent)
+                    .max(0);
+                n.state.noise * carry / 100
+            })
+            .max()
+            .unwrap_or(0);
+        new_state.noise = (self.noise * config.noise_linger_percent / 100)
+            .max(heard)
+            .max(battle_noise);
+
+        // Panic spreads from where the humans fell, remembering which way it came from
+        let (panic, panic_source) = if self.status.is_human() && new_state.status.is_zombie() {
+            (config.panic_strength, 8)
+        } else {
+            around
+                .iter()
+                .map(|n| (n.state.panic - config.panic_falloff, n.direction))
+                .chain(std::iter::once((
+                    self.panic - config.panic_decay,
+                    self.panic_source,
+                )))
+                .max_by_key(|(panic, _)| *panic)
+                .unwrap_or((0, 8))
+        };
+        (new_state.panic, new_state.panic_source) = if panic > 0 {
+            (panic, panic_source)
+        } else {
+            (0, 8)
+        };
+
+        // Humans here or looking in from a neighbor see the real smell, elsewhere the memory fades
+        let observed = self.status.is_living()
+            || around
+                .iter()
+                .any(|n| n.state.status.is_living() && -n.altitude_delta <= config.sight_max_climb);
+        new_state.intel_zombie = if observed && self.weather == Weather::Fog {
+            new_state.smell_zombie * (100 - config.fog_sight_loss_percent) / 100
+        } else if observed {
+            new_state.smell_zombie
+        } else {
+            self.intel_zombie * (100 - config.intel_decay_percent) / 100
+        };
+
+        // Survivors too few to fight lie low until the zombies are gone
+        new_state.hiding = new_state.status.is_human()
+            && new_state.population < config.hide_max_population
+            && new_state.smell_zombie > 0;
 
         // Finally, look at the smells of neighbors to determine our next direction
-        new_state.direction = 8; // Default to no direction
+        new_state.stay(); // Default to no direction
+        new_state.shamble = 0;
+        // Snow holds back some of the ones who'd go
+        let move_percent = if self.weather == Weather::Snow {
+            (config.move_percent * (100 - config.snow_slow_percent) / 100).max(1)
+        } else {
+            config.move_percent
+        };
 
-        // If we're zombies, mindlessly follow the strongest smell of humans.
+        // If we're zombies, mindlessly follow the sound of battle, or else the strongest smell of humans.
         // If we're humans, hunker down unless we detect a zombie population significantly smaller than ours.
         match new_state.status {
             Status::Zombie => {
-                let preferred_neighbor = neighbors
+                // Hidden survivors are passed by, unless the horde is big enough to stumble upon them
+                // The same roll for the same cell state, so seeded runs repeat
+                let roll = scramble(
+                    (((self.xy.y as u64) << 32) | self.xy.x as u32 as u64)
+                        ^ scramble(
+                            ((self.population as u64) << 32) | self.smell_human as u32 as u64,
+                        ),
+                ) % config.hide_detection_roll as u64;
+                let seeking = if self.night {
+                    new_state.population * (100 + config.night_detection_bonus_percent) / 100
+                } else {
+                    new_state.population
+                };
+                let spotted = seeking as u64 > roll;
+                let candidates: Vec<&Neighbor> = around
                     .iter()
-                    .max_by(|n1, n2| {
-                        match n1.smell_human.cmp(&n2.smell_human) {
+                    .filter(|n| !n.state.terrain.is_impassable() && (!n.state.hiding || spotted))
+                    .collect();
+                // Anything too faint to make out is no louder than silence
+                let heard = |n: &Self| {
+                    if n.noise >= config.noise_heard_min {
+                        n.noise
+                    } else {
+                        0
+                    }
+                };
+                let rank = |a: &&Neighbor, b: &&Neighbor| {
+                    let (n1, n2) = (a.state, b.state);
+                    match heard(n1).cmp(&heard(n2)) {
+                        Ordering::Equal => match n1.smell_human.cmp(&n2.smell_human) {
                             Ordering::Equal => {
                                 match n1.temperature.cmp(&n2.temperature) {
                                     Ordering::Equal => {
@@ -202,27 +828,79 @@ impl CellState for ZombieState {
                                 }
                             }
                             non_eq => non_eq,
-                        }
-                    })
-                    .unwrap();
+                        },
+                        non_eq => non_eq, // the sound of battle draws them over any smell
+                    }
+                };
+                let Some(preferred) = candidates.iter().copied().max_by(rank) else {
+                    return new_state;
+                };
 
-                new_state.direction = delta_to_direction(preferred_neighbor.xy - self.xy).unwrap();
+                // Every way that draws them as much gets its share of the horde,
+                // with nothing to follow at all the idle behavior decides
+                let idle = heard(preferred.state) == 0 && preferred.state.smell_human == 0;
+                let ways = match config.zombie_idle {
+                    _ if !idle => tied_with(&candidates, preferred, rank),
+                    ZombieIdle::Drift => tied_with(&candidates, preferred, rank),
+                    ZombieIdle::Wander => {
+                        let pick = scramble(
+                            (((self.xy.y as u64) << 32) | self.xy.x as u32 as u64)
+                                ^ scramble(
+                                    ((self.population as u64) << 32)
+                                        | self.smell_zombie as u32 as u64,
+                                ),
+                        ) % candidates.len() as u64;
+                        vec![candidates[pick as usize].direction]
+                    }
+                    ZombieIdle::Stay => Vec::new(),
+                    ZombieIdle::TowardNoise => candidates
+                        .iter()
+                        .filter(|n| n.state.noise > 0)
+                        .max_by_key(|n| n.state.noise)
+                        .map(|n| vec![n.direction])
+                        .unwrap_or_default(),
+                };
+                // Runners never hang back
+                let runners = new_state.variants[0];
+                new_state.head(&ways, move_percent + runners * (100 - move_percent) / 100);
+
+                // Frozen zombies shamble, only acting on every few ticks
+                let move_every = if self.terrain == TerrainKind::Road {
+                    (config.frozen_move_every as i32 * 100 / config.road_throughput_percent) as u8
+                } else {
+                    config.frozen_move_every
+                };
+                if self.temperature < config.freezing_temperature && self.shamble + 1 < move_every {
+                    new_state.shamble = self.shamble + 1;
+                    new_state.stay();
+                }
             }
             Status::Human => {
-                let preferred_neighbor = neighbors
+                let candidates: Vec<&Neighbor> = around
                     .iter()
-                    .max_by(|n1, n2| match n1.smell_zombie.cmp(&n2.smell_zombie) {
-                        Ordering::Equal => {
-                            match n1.temperature.cmp(&n2.temperature) {
-                                Ordering::Equal => {
-                                    n1.altitude.cmp(&n2.altitude) // people prefer higher places, it's a zombie apoc, high is safer!
+                    .filter(|n| !n.state.terrain.is_impassable())
+                    .collect();
+                let rank = |a: &&Neighbor, b: &&Neighbor| {
+                    let (n1, n2) = (a.state, b.state);
+                    match n1.intel_zombie.cmp(&n2.intel_zombie) {
+                        Ordering::Equal => match n1.chokepoint.cmp(&n2.chokepoint) {
+                            Ordering::Equal => {
+                                match n1.temperature.cmp(&n2.temperature) {
+                                    Ordering::Equal => {
+                                        n1.altitude.cmp(&n2.altitude) // people prefer higher places, it's a zombie apoc, high is safer!
+                                    }
+                                    non_eq => non_eq, // people prefer warmer places
                                 }
-                                non_eq => non_eq, // people prefer warmer places
                             }
-                        }
+                            non_eq => non_eq, // people prefer garrisoning chokepoints
+                        },
                         non_eq => non_eq.reverse(), // people prefer places with less zombie smell, this is ImPoRtAnT! (for living to see another day)
-                    })
-                    .unwrap();
+                    }
+                };
+                let Some(preferred) = candidates.iter().copied().max_by(rank) else {
+                    return new_state;
+                };
+                let preferred_neighbor = preferred.state;
 
                 let preferred_neighbor_zombie_population = if preferred_neighbor.status.is_zombie()
                 {
@@ -233,13 +911,48 @@ impl CellState for ZombieState {
 
                 // Move to min zombie smell cell if either
                 // We outnumber zombies > 3:1 - attack!
-                // It has smaller than ours zombie smell - it's probably a safer cell than ours.
-                if (new_state.population / 3 > preferred_neighbor_zombie_population)
-                    || (!preferred_neighbor.status.is_zombie()
-                        && preferred_neighbor.smell_zombie < new_state.smell_zombie)
-                {
-                    new_state.direction =
-                        delta_to_direction(preferred_neighbor.xy - self.xy).unwrap();
+                // It has smaller than ours zombie smell (as far as we know) - it's probably a safer cell than ours.
+                // A chokepoint garrison, or a region told to defend, holds its ground and only leaves to attack.
+                // A region told to evacuate falls back whenever it can and never attacks.
+                let holding = match self.objective {
+                    Objective::None => self.chokepoint,
+                    Objective::Defend => true,
+                    Objective::Evacuate => false,
+                };
+                // The hungry leave to look for food elsewhere, garrison or not
+                let garrisoned = holding && !hungry && !preferred_neighbor.status.is_zombie();
+                let moving = match self.objective {
+                    _ if hungry => !preferred_neighbor.status.is_zombie(),
+                    Objective::Evacuate => !preferred_neighbor.status.is_zombie(),
+                    _ => {
+                        (new_state.population / config.attack_ratio
+                            > preferred_neighbor_zombie_population)
+                            || (!preferred_neighbor.status.is_zombie()
+                                && preferred_neighbor.intel_zombie < new_state.smell_zombie)
+                    }
+                };
+                if !garrisoned && moving {
+                    new_state.head(&tied_with(&candidates, preferred, rank), move_percent);
+                }
+
+                // Panic overrides the above, scatter directly away from where it came from
+                if new_state.panic > 0 && new_state.panic_source != 8 {
+                    let away = (new_state.panic_source + 4) % 8;
+                    let open: Vec<i8> = [away, (away + 1) % 8, (away + 7) % 8]
+                        .into_iter()
+                        .filter(|&direction| match self.toward(&around, direction) {
+                            Some(n) => !n.state.terrain.is_impassable(),
+                            None => self.open_edge, // off the map
+                        })
+                        .collect();
+                    if !open.is_empty() {
+                        new_state.head(&open, move_percent);
+                    }
+                }
+
+                // Hiding survivors don't give themselves away
+                if new_state.hiding {
+                    new_state.stay();
                 }
             }
             _ => {}
@@ -258,27 +971,311 @@ impl CellState for ZombieState {
     }
 }
 
-impl From<Vec<i32>> for ZombieState {
-    fn from(vec: Vec<i32>) -> Self {
-        ZombieState {
-            xy: IVec2::new(vec[0], vec[1]),
-            altitude: vec[2],
-            temperature: vec[3],
-            status: match vec[4] {
-                1 => Status::Zombie,
-                2 => Status::Human,
-                _ => Status::Empty,
-            },
-            population: vec[5],
-            direction: vec[6] as i8,
-            smell_human: vec[7],
-            smell_zombie: vec[8],
+/// A neighbor as seen from the cell it is around.
+#[derive(Debug, Clone, Copy)]
+pub struct Neighbor<'a> {
+    pub direction: i8, // toward the neighbor
+    pub state: &'a ZombieState,
+    pub altitude_delta: i32, // the neighbor's altitude minus the cell's
+    pub distance: f32,       // between the cell centers, in cells
+}
+
+impl Neighbor<'_> {
+    /// Part of the neighbor's group it is sending to the cell it is around.
+    pub fn sending_here(&self) -> i32 {
+        self.state.sent_toward((self.direction + 4) % 8)
+    }
+
+    /// Altitude gained per cell of distance going to the neighbor.
+    pub fn slope(&self) -> f32 {
+        self.altitude_delta as f32 / self.distance
+    }
+}
+
+impl ZombieState {
+    /// The `neighbors` of this cell with which way and how far each one is, in their order.
+    pub fn around<'a>(&self, neighbors: &[&'a ZombieState]) -> impl Iterator<Item = Neighbor<'a>> {
+        neighbors.iter().filter_map(|&state| {
+            let direction = delta_to_direction(state.xy - self.xy).filter(|&d| d != 8)?;
+            Some(Neighbor {
+                direction,
+                state,
+                altitude_delta: state.altitude - self.altitude,
+                distance: if direction % 2 == 0 {
+                    1.0
+                } else {
+                    std::f32::consts::SQRT_2
+                },
+            })
+        })
+    }
+
+    /// Of the human population, the bitten who haven't turned yet.
+    pub fn infected(&self) -> i32 {
+        self.incubating.iter().sum()
+    }
+
+    /// Part of the population setting off this tick, none when it stays.
+    pub fn moving_population(&self) -> i32 {
+        (self.population * self.outflow.iter().sum::<i32>() + 99) / 100
+    }
+
+    /// Part of the population sent toward the neighbor in `direction`. Shares are rounded up
+    /// one after another, so lone survivors still go somewhere but no more than everyone leaves.
+    pub fn sent_toward(&self, direction: i8) -> i32 {
+        if !(0..8).contains(&direction) {
+            return 0;
+        }
+        let before: i32 = self.outflow[..direction as usize].iter().sum();
+        let sent_up_to = |percent: i32| (self.population * percent + 99) / 100;
+        sent_up_to(before + self.outflow[direction as usize]) - sent_up_to(before)
+    }
+
+    /// Sends `percent` of the population off split evenly between `directions`, the first one
+    /// gets what doesn't split and becomes the direction.
+    pub fn head(&mut self, directions: &[i8], percent: i32) {
+        self.stay();
+        let Some(&first) = directions.first() else {
+            return;
+        };
+        let share = percent / directions.len() as i32;
+        for &direction in directions {
+            self.outflow[direction as usize] += share;
+        }
+        self.outflow[first as usize] += percent - share * directions.len() as i32;
+        self.direction = first;
+    }
+
+    pub fn stay(&mut self) {
+        self.direction = 8;
+        self.outflow = [0; 8];
+    }
+
+    /// The neighbor in `direction`, none past the edge of the map.
+    pub fn toward<'a>(&self, around: &[Neighbor<'a>], direction: i8) -> Option<Neighbor<'a>> {
+        around.iter().find(|n| n.direction == direction).copied()
+    }
+
+    /// `smell` here after part of it is swapped for the neighbors' average (weighted toward
+    /// upwind) and some fades, rounded down once so weak gradients survive.
+    fn diffused(
+        &self,
+        around: &[Neighbor],
+        smell: fn(&Self) -> i32,
+        config: &SimulationConfig,
+    ) -> i32 {
+        let weight = |n: &Neighbor| config.wind.weight_percent(n.direction) as i64;
+        let weights = around.iter().map(weight).sum::<i64>().max(1);
+        let carried: i64 = around
+            .iter()
+            .map(|n| smell(n.state) as i64 * weight(n))
+            .sum();
+        let spread = config.smell_spread_percent as i64;
+        let mixed = smell(self) as i64 * (100 - spread) * weights + carried * spread; // x100 x weights
+        (mixed * self.smell_retention_percent(config) as i64 / (weights * 100 * 100)) as i32
+    }
+
+    /// Share of the smell kept here each tick, dry ground loses more of it and walls most.
+    fn smell_retention_percent(&self, config: &SimulationConfig) -> i32 {
+        let dryness = (100 - self.moisture) / 2; // 0 on the wettest ground, 100 on the driest
+        let washout = if self.weather == Weather::Rain {
+            config.rain_washout_percent
+        } else {
+            0
+        };
+        let retention = (100
+            - config.smell_decay_percent
+            - dryness * config.dry_smell_loss_percent / 100
+            - washout)
+            .max(0);
+        if self.terrain == TerrainKind::Wall {
+            retention * config.wall_smell_percent / 100
+        } else {
+            retention
         }
     }
 }
 
+/// Builds the state of a freshly generated cell, anything not set starts empty.
+#[derive(Clone)]
+pub struct ZombieStateBuilder(ZombieState);
+
+impl ZombieState {
+    pub fn builder(xy: IVec2) -> ZombieStateBuilder {
+        ZombieStateBuilder::new(xy)
+    }
+}
+
+impl ZombieStateBuilder {
+    pub fn new(xy: IVec2) -> Self {
+        ZombieStateBuilder(ZombieState {
+            xy,
+            direction: 8,    // staying
+            panic_source: 8, // none
+            supplied: true,
+            ..Default::default()
+        })
+    }
+
+    // Terrain layers, each in -100..100
+
+    pub fn altitude(mut self, altitude: i32) -> Self {
+        self.0.altitude = altitude;
+        self
+    }
+
+    pub fn temperature(mut self, temperature: i32) -> Self {
+        self.0.temperature = temperature;
+        self
+    }
+
+    pub fn moisture(mut self, moisture: i32) -> Self {
+        self.0.moisture = moisture;
+        self
+    }
+
+    pub fn terrain(mut self, terrain: TerrainKind) -> Self {
+        self.0.terrain = terrain;
+        self
+    }
+
+    pub fn biome(mut self, biome: Biome) -> Self {
+        self.0.biome = biome;
+        self
+    }
+
+    pub fn chokepoint(mut self, chokepoint: bool) -> Self {
+        self.0.chokepoint = chokepoint;
+        self
+    }
+
+    pub fn open_edge(mut self, open_edge: bool) -> Self {
+        self.0.open_edge = open_edge;
+        self
+    }
+
+    // Who lives here
+
+    pub fn status(mut self, status: Status) -> Self {
+        self.0.status = status;
+        self
+    }
+
+    pub fn population(mut self, population: i32) -> Self {
+        self.0.population = population;
+        self
+    }
+
+    /// The city or outbreak the population comes from.
+    pub fn lineage(mut self, lineage: u32) -> Self {
+        self.0.lineage = lineage;
+        self
+    }
+
+    pub fn faction(mut self, faction: u8) -> Self {
+        self.0.faction = faction;
+        self
+    }
+
+    /// Percent of the zombies that are runners, tanks and screamers.
+    pub fn variants(mut self, variants: [i32; 3]) -> Self {
+        self.0.variants = variants;
+        self
+    }
+
+    pub fn food(mut self, food: i32) -> Self {
+        self.0.food = food;
+        self
+    }
+
+    /// The config the cell is updated with, the default one unless set.
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.0.rules = rules;
+        self
+    }
+
+    /// The cell, unless it breaks an invariant or a layer is out of range.
+    pub fn build(self) -> Result<ZombieState> {
+        let state = self.0;
+        let layers = [
+            ("altitude", state.altitude),
+            ("temperature", state.temperature),
+            ("moisture", state.moisture),
+        ];
+        let problem = violation(&state)
+            .or_else(|| {
+                layers
+                    .iter()
+                    .find(|(_, value)| !(-100..=100).contains(value))
+                    .map(|(layer, value)| format!("{layer} {value} out of -100..100"))
+            })
+            .or_else(|| (state.food < 0).then(|| format!("negative food {}", state.food)));
+        match problem {
+            Some(problem) => Err(Error::Invalid(format!("cell {}: {problem}", state.xy))),
+            None => Ok(state),
+        }
+    }
+}
+
+/// Broken invariant of a single cell, if any, also used to check loaded saves and
+/// by the soak runs.
+pub fn violation(state: &ZombieState) -> Option<String> {
+    if state.population < 0 {
+        return Some(format!("negative population {}", state.population));
+    }
+    if state.status.is_empty() != (state.population == 0) {
+        return Some(format!(
+            "status {:?} with population {}",
+            state.status, state.population
+        ));
+    }
+    if state.terrain.is_impassable() && state.population > 0 {
+        return Some(format!(
+            "population {} on impassable {:?}",
+            state.population, state.terrain
+        ));
+    }
+    if !(0..=8).contains(&state.direction) {
+        return Some(format!("direction {} out of range", state.direction));
+    }
+    if state.outflow.iter().any(|&percent| percent < 0) || state.outflow.iter().sum::<i32>() > 100 {
+        return Some(format!(
+            "outflow {:?} not a share of the population",
+            state.outflow
+        ));
+    }
+    None
+}
+
+/// Drops the infected the `population` doesn't have, the last bitten first.
+fn keep_infected(incubating: &mut [i32; MAX_INCUBATION_TICKS], population: i32) {
+    let mut left = population;
+    for bitten in incubating.iter_mut() {
+        *bitten = (*bitten).min(left);
+        left -= *bitten;
+    }
+}
+
+/// Directions of the `candidates` ranked as high as `best`, the best one first.
+fn tied_with<'a>(
+    candidates: &[&Neighbor<'a>],
+    best: &Neighbor<'a>,
+    rank: impl Fn(&&Neighbor<'a>, &&Neighbor<'a>) -> Ordering,
+) -> Vec<i8> {
+    std::iter::once(best.direction)
+        .chain(
+            candidates
+                .iter()
+                .filter(|&&n| n.direction != best.direction && rank(&n, &best) == Ordering::Equal)
+                .map(|n| n.direction),
+        )
+        .collect()
+}
+
 pub fn delta_to_direction(delta: IVec2) -> Option<i8> {
-    match (delta.x, delta.y) {
+    // Neighbors across the edge of a wrapping map are a whole map apart
+    let wrapped = |d: i32| if d.abs() > 1 { -d.signum() } else { d };
+    match (wrapped(delta.x), wrapped(delta.y)) {
         (0, -1) => Some(0),  // North
         (1, -1) => Some(1),  // Northeast
         (1, 0) => Some(2),   // East
@@ -291,3 +1288,613 @@ pub fn delta_to_direction(delta: IVec2) -> Option<i8> {
         _ => None,
     } // Faster than a loop in 87% of cases, and more readable
 }
+
+pub fn direction_to_delta(direction: i8) -> Option<IVec2> {
+    let (x, y) = match direction {
+        0 => (0, -1),
+        1 => (1, -1),
+        2 => (1, 0),
+        3 => (1, 1),
+        4 => (0, 1),
+        5 => (-1, 1),
+        6 => (-1, 0),
+        7 => (-1, -1),
+        8 => (0, 0),
+        _ => return None,
+    };
+    Some(IVec2::new(x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(x: i32, y: i32) -> ZombieState {
+        let mut state = ZombieState {
+            xy: IVec2::new(x, y),
+            panic_source: 8,
+            ..ZombieState::default()
+        };
+        state.stay();
+        state
+    }
+
+    /// Empty ground all around (1, 1).
+    fn surroundings() -> Vec<ZombieState> {
+        (0..3)
+            .flat_map(|y| (0..3).map(move |x| cell(x, y)))
+            .filter(|state| state.xy != IVec2::ONE)
+            .collect()
+    }
+
+    fn settled_humans() -> ZombieState {
+        let config = SimulationConfig::default();
+        ZombieState {
+            status: Status::Human,
+            population: 1000,
+            calm_ticks: config.birth_calm_ticks,
+            food: config.birth_min_food,
+            ..cell(1, 1)
+        }
+    }
+
+    #[test]
+    fn calm_fed_humans_grow() {
+        let ledger = settled_humans()
+            .new_cell_state(surroundings().iter())
+            .ledger;
+        assert!(ledger.births > 0);
+    }
+
+    #[test]
+    fn humans_that_fought_or_moved_lately_dont_grow() {
+        let config = SimulationConfig::default();
+        let fought = ZombieState {
+            calm_ticks: config.birth_calm_ticks - 2,
+            ..settled_humans()
+        };
+        assert_eq!(
+            fought.new_cell_state(surroundings().iter()).ledger.births,
+            0
+        );
+
+        let mut moving = settled_humans();
+        moving.head(&[2], 10);
+        assert_eq!(
+            moving.new_cell_state(surroundings().iter()).ledger.births,
+            0
+        );
+    }
+
+    #[test]
+    fn hungry_humans_dont_grow() {
+        let hungry = ZombieState {
+            food: SimulationConfig::default().birth_min_food - 1,
+            ..settled_humans()
+        };
+        assert_eq!(
+            hungry.new_cell_state(surroundings().iter()).ledger.births,
+            0
+        );
+    }
+
+    #[test]
+    fn the_infected_turn_once_their_time_runs_out() {
+        let mut incubating = [0; MAX_INCUBATION_TICKS];
+        incubating[2] = 10;
+        let mut state = ZombieState {
+            incubating,
+            ..settled_humans()
+        };
+        let conversions: Vec<i32> = (0..4)
+            .map(|_| {
+                state = state.new_cell_state(surroundings().iter());
+                state.stay();
+                state.ledger.conversions
+            })
+            .collect();
+        assert_eq!(conversions, [0, 0, 10, 0]);
+        assert_eq!(state.infected(), 0);
+        assert_eq!(state.status, Status::Human);
+    }
+
+    /// The cells of `grid` next to `xy`.
+    fn neighbors_of(grid: &[ZombieState], xy: IVec2) -> Vec<ZombieState> {
+        grid.iter()
+            .filter(|state| state.xy != xy && (state.xy - xy).abs().max_element() == 1)
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn as_many_zombies_are_cured_as_the_humans_next_to_them_take_in() {
+        let mut grid: Vec<ZombieState> = (0..3)
+            .flat_map(|y| {
+                (0..4).map(move |x| ZombieState {
+                    cured: true,
+                    ..cell(x, y)
+                })
+            })
+            .collect();
+        grid[5] = ZombieState {
+            status: Status::Zombie,
+            population: 1000,
+            ..grid[5].clone()
+        };
+        grid[6] = ZombieState {
+            status: Status::Human,
+            population: 100,
+            ..grid[6].clone()
+        };
+        grid[7] = ZombieState {
+            status: Status::Human,
+            population: 100,
+            ..grid[7].clone()
+        };
+        let next: Vec<ZombieState> = grid
+            .iter()
+            .map(|state| state.new_cell_state(neighbors_of(&grid, state.xy).iter()))
+            .collect();
+
+        let config = SimulationConfig::default();
+        // Only the humans right next to the zombies at (1, 1) bring some back
+        assert_eq!(next[5].ledger.cured, 1000 * config.cure_percent / 100);
+        assert_eq!(next[6].ledger.recovered, next[5].ledger.cured);
+        assert_eq!(next[7].ledger.recovered, 0);
+    }
+
+    fn zombies(temperature: i32) -> ZombieState {
+        ZombieState {
+            status: Status::Zombie,
+            population: 1000,
+            temperature,
+            ..cell(1, 1)
+        }
+    }
+
+    /// Whether a zombie cell at `temperature`, with humans to the east, made a move on each of
+    /// the first `ticks` ticks. They are held back after every move, so it's the same horde.
+    fn moves(temperature: i32, ticks: usize) -> Vec<bool> {
+        let mut around = surroundings();
+        for neighbor in around.iter_mut().filter(|n| n.xy == IVec2::new(2, 1)) {
+            neighbor.smell_human = 10;
+        }
+        let mut state = zombies(temperature);
+        (0..ticks)
+            .map(|_| {
+                state = state.new_cell_state(around.iter());
+                let moved = state.direction != 8;
+                state.stay();
+                moved
+            })
+            .collect()
+    }
+
+    #[test]
+    fn frozen_zombies_only_move_every_few_ticks() {
+        let config = SimulationConfig::default();
+        let every = config.frozen_move_every as usize;
+        let frozen = moves(config.freezing_temperature - 1, every * 3);
+        for (tick, moved) in frozen.into_iter().enumerate() {
+            assert_eq!(moved, (tick + 1) % every == 0, "tick {tick}");
+        }
+        assert!(
+            moves(config.freezing_temperature, every * 3)
+                .into_iter()
+                .all(|moved| moved)
+        );
+    }
+
+    #[test]
+    fn soldiers_next_door_shoot_more_zombies_by_day() {
+        let config = SimulationConfig::default();
+        let mut around = surroundings();
+        around[4] = ZombieState {
+            status: Status::Military,
+            population: 100,
+            ..around[4].clone()
+        };
+        let shot = |night: bool| {
+            let state = ZombieState {
+                night,
+                ..zombies(0)
+            };
+            state.new_cell_state(around.iter()).ledger.zombie_deaths
+        };
+        assert_eq!(shot(true), 100 * config.military_fire_percent / 100);
+        assert_eq!(
+            shot(false),
+            100 * (config.military_fire_percent * (100 + config.day_fire_bonus_percent) / 100)
+                / 100
+        );
+    }
+
+    #[test]
+    fn zombies_rotting_in_the_heat_are_counted_as_decayed() {
+        let config = SimulationConfig::default();
+        let hot = zombies(config.heat_temperature + 1);
+        let new_state = hot.new_cell_state(surroundings().iter());
+        let decayed = hot.population * config.heat_decay_percent / 100;
+        assert_eq!(new_state.ledger.decayed, decayed);
+        assert_eq!(new_state.population, hot.population - decayed);
+
+        let mild = zombies(config.heat_temperature);
+        assert_eq!(mild.new_cell_state(surroundings().iter()).ledger.decayed, 0);
+    }
+
+    #[test]
+    fn panic_sends_humans_away_from_where_it_came_from() {
+        let config = SimulationConfig::default();
+        let mut around = surroundings();
+        // The humans to the east just fell
+        around[4].panic = config.panic_strength;
+        let humans = ZombieState::builder(IVec2::ONE)
+            .status(Status::Human)
+            .population(100)
+            .build()
+            .unwrap();
+        let new_state = humans.new_cell_state(around.iter());
+        assert_eq!(
+            new_state.panic,
+            config.panic_strength - config.panic_falloff
+        );
+        assert_eq!(new_state.panic_source, 2);
+        // West, and southwest and northwest beside it
+        assert_eq!(new_state.direction, 6);
+        for direction in [5, 6, 7] {
+            assert!(
+                new_state.sent_toward(direction) > 0,
+                "direction {direction}"
+            );
+        }
+        for direction in [0, 1, 2, 3, 4] {
+            assert_eq!(new_state.sent_toward(direction), 0, "direction {direction}");
+        }
+    }
+
+    /// Well fed humans at (1, 1) with `arriving` more coming in from the east.
+    fn taking_in(population: i32, arriving: i32) -> ZombieState {
+        let mut around = surroundings();
+        around[4] = ZombieState {
+            status: Status::Human,
+            population: arriving,
+            ..around[4].clone()
+        };
+        around[4].head(&[6], 100);
+        ZombieState::builder(IVec2::ONE)
+            .status(Status::Human)
+            .population(population)
+            .food(SimulationConfig::default().food_capacity)
+            .build()
+            .unwrap()
+            .new_cell_state(around.iter())
+    }
+
+    #[test]
+    fn a_flood_of_refugees_overcrowds_the_cell_into_a_camp() {
+        let config = SimulationConfig::default();
+        let flooded = taking_in(config.cell_capacity, config.camp_min_inflow);
+        assert!(flooded.camp);
+        let crowd = config.cell_capacity + config.camp_min_inflow;
+        assert_eq!(
+            flooded.ledger.disease_deaths,
+            crowd * config.camp_disease_percent / 100
+        );
+        assert_eq!(flooded.population, crowd - flooded.ledger.disease_deaths);
+
+        // A trickle of them, or room enough for the flood, isn't a camp
+        let trickle = taking_in(config.cell_capacity, config.camp_min_inflow - 1);
+        assert!(!trickle.camp);
+        assert_eq!(trickle.ledger.disease_deaths, 0);
+        let room = config.cell_capacity - config.camp_min_inflow;
+        assert!(!taking_in(room, config.camp_min_inflow).camp);
+    }
+
+    /// Cut off humans at (1, 1) surrounded by zombies, after `ticks` of it, with `attacking` of
+    /// them coming in from the east.
+    fn encircled(ticks: u8, attacking: i32) -> ZombieState {
+        let mut around: Vec<ZombieState> = surroundings()
+            .into_iter()
+            .map(|neighbor| ZombieState {
+                status: Status::Zombie,
+                population: attacking.max(1),
+                ..neighbor
+            })
+            .collect();
+        if attacking > 0 {
+            around[4].head(&[6], 100);
+        }
+        let humans = ZombieState::builder(IVec2::ONE)
+            .status(Status::Human)
+            .population(100)
+            .build()
+            .unwrap();
+        ZombieState {
+            supplied: false,
+            encircled: ticks,
+            night: true,
+            ..humans
+        }
+        .new_cell_state(around.iter())
+    }
+
+    #[test]
+    fn humans_encircled_long_enough_surrender() {
+        let config = SimulationConfig::default();
+        assert_eq!(encircled(0, 0).encircled, 1);
+
+        // Still fighting back, the usual share of them rise
+        let holding_out = encircled(config.surrender_after_ticks - 1, 300);
+        assert_eq!(holding_out.status, Status::Zombie);
+        assert_eq!(
+            holding_out.ledger.conversions,
+            100 / config.conversion_divisor
+        );
+        assert_eq!(
+            holding_out.ledger.zombie_deaths,
+            100 * config.cut_off_holder_advantage
+        );
+
+        // Given up, they fight with no advantage and more of them rise
+        let surrendered = encircled(config.surrender_after_ticks, 300);
+        assert_eq!(surrendered.status, Status::Zombie);
+        assert_eq!(
+            surrendered.ledger.conversions,
+            100 * config.surrender_turned_percent / 100
+        );
+        assert_eq!(surrendered.ledger.zombie_deaths, 100);
+    }
+
+    #[test]
+    fn a_few_humans_smelling_zombies_hide_and_give_off_no_smell() {
+        let mut around = surroundings();
+        around[4].smell_zombie = 10 * SMELL_SCALE;
+        let humans = ZombieState::builder(IVec2::ONE)
+            .status(Status::Human)
+            .population(SimulationConfig::default().hide_max_population - 1)
+            .build()
+            .unwrap();
+        let lying_low = humans.new_cell_state(around.iter());
+        assert!(lying_low.hiding);
+        assert_eq!(lying_low.direction, 8);
+        let smell_later = |hiding: bool| {
+            let state = ZombieState {
+                hiding,
+                smell_human: 0,
+                ..lying_low.clone()
+            };
+            state.new_cell_state(surroundings().iter()).smell_human
+        };
+        assert_eq!(smell_later(true), 0);
+        assert!(smell_later(false) > 0);
+    }
+
+    #[test]
+    fn zombies_pass_hiding_humans_by_unless_enough_of_them_come_looking() {
+        let mut around = surroundings();
+        around[4] = ZombieState {
+            status: Status::Human,
+            population: 10,
+            hiding: true,
+            smell_human: 10 * SMELL_SCALE,
+            ..around[4].clone()
+        };
+        let horde = |population: i32| ZombieState {
+            population,
+            ..zombies(0)
+        };
+        let passing = horde(1).new_cell_state(around.iter());
+        assert_eq!(passing.sent_toward(2), 0);
+        let searching =
+            horde(SimulationConfig::default().hide_detection_roll).new_cell_state(around.iter());
+        assert_eq!(searching.direction, 2);
+    }
+
+    /// What's left of a group of 100 as it arrives at empty (1, 1) from the east,
+    /// coming from `altitude` above it.
+    fn crossing(status: Status, altitude: i32) -> ZombieState {
+        let mut around = surroundings();
+        around[4] = ZombieState {
+            status,
+            population: 100,
+            altitude,
+            ..around[4].clone()
+        };
+        around[4].head(&[6], 100);
+        ZombieState::builder(IVec2::ONE)
+            .build()
+            .unwrap()
+            .new_cell_state(around.iter())
+    }
+
+    #[test]
+    fn groups_crossing_steep_ground_lose_some_on_the_way() {
+        let config = SimulationConfig::default();
+        let gentle = crossing(Status::Human, config.crossing_free_step);
+        assert_eq!(gentle.population, 100);
+        assert_eq!(gentle.ledger.stragglers, 0);
+
+        let step = config.crossing_free_step + 4;
+        let loss = 4 * config.crossing_loss_percent;
+        let descending = crossing(Status::Human, step);
+        assert_eq!(descending.population, 100 - loss);
+        assert_eq!(descending.ledger.stragglers, loss);
+        assert_eq!(crossing(Status::Zombie, step).population, 100 - loss);
+
+        // Zombies climb worse than they come down
+        let climbing = crossing(Status::Zombie, -step);
+        let loss =
+            (loss + step * config.zombie_climb_loss_percent).min(config.crossing_max_loss_percent);
+        assert_eq!(climbing.population, 100 - loss);
+        assert_eq!(climbing.ledger.stragglers, loss);
+    }
+
+    #[test]
+    fn zombies_drawn_as_much_two_ways_split_between_them() {
+        let config = SimulationConfig::default();
+        let mut around = surroundings();
+        for neighbor in around
+            .iter_mut()
+            .filter(|n| n.xy == IVec2::new(2, 1) || n.xy == IVec2::new(1, 2))
+        {
+            neighbor.smell_human = 10;
+        }
+        let horde = zombies(0).new_cell_state(around.iter());
+        let share = 1000 * config.move_percent / 100 / 2;
+        assert_eq!(horde.sent_toward(2), share);
+        assert_eq!(horde.sent_toward(4), share);
+        assert_eq!(horde.moving_population(), share * 2);
+
+        // Each of them takes in its own share
+        let east = ZombieState::builder(IVec2::new(2, 1))
+            .build()
+            .unwrap()
+            .new_cell_state(std::iter::once(&horde));
+        assert_eq!(east.status, Status::Zombie);
+        assert_eq!(east.population, share);
+    }
+
+    #[test]
+    fn what_doesnt_split_evenly_goes_the_first_way() {
+        let mut state = ZombieState {
+            population: 100,
+            ..cell(1, 1)
+        };
+        state.head(&[4, 0, 2], 50);
+        assert_eq!(state.direction, 4);
+        assert_eq!(state.outflow, [16, 0, 16, 0, 18, 0, 0, 0]);
+        assert_eq!(state.moving_population(), 50);
+    }
+
+    #[test]
+    fn soldiers_hold_where_as_many_civilians_fall() {
+        let config = SimulationConfig::default();
+        let mut around = surroundings();
+        around[4] = ZombieState {
+            status: Status::Zombie,
+            population: 600,
+            ..around[4].clone()
+        };
+        around[4].head(&[6], 100);
+        let attacked = |status: Status| {
+            let defenders = ZombieState::builder(IVec2::ONE)
+                .status(status)
+                .population(100)
+                .build()
+                .unwrap();
+            ZombieState {
+                night: true,
+                ..defenders
+            }
+            .new_cell_state(around.iter())
+        };
+        let attackers = 600 * (100 + config.night_attack_bonus_percent) / 100;
+
+        let civilians = attacked(Status::Human);
+        assert_eq!(civilians.status, Status::Zombie);
+        assert_eq!(
+            civilians.ledger.zombie_deaths,
+            100 * config.holder_advantage
+        );
+
+        let soldiers = attacked(Status::Military);
+        assert_eq!(soldiers.status, Status::Military);
+        let advantage = config.holder_advantage * config.military_combat_multiplier;
+        assert_eq!(soldiers.ledger.human_deaths, attackers / advantage);
+        assert_eq!(soldiers.ledger.zombie_deaths, attackers);
+    }
+
+    fn variant(variants: [i32; 3], population: i32) -> ZombieState {
+        ZombieState::builder(IVec2::ONE)
+            .status(Status::Zombie)
+            .population(population)
+            .variants(variants)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn runners_never_hang_back() {
+        let config = SimulationConfig::default();
+        let mut around = surroundings();
+        around[4].smell_human = 10;
+        let moving = |variants| {
+            variant(variants, 1000)
+                .new_cell_state(around.iter())
+                .moving_population()
+        };
+        assert_eq!(moving([0, 0, 0]), 1000 * config.move_percent / 100);
+        assert_eq!(moving([100, 0, 0]), 1000);
+    }
+
+    #[test]
+    fn tanks_hold_where_as_many_walkers_are_overrun() {
+        let mut around = surroundings();
+        around[4] = ZombieState {
+            status: Status::Human,
+            population: 500,
+            ..around[4].clone()
+        };
+        around[4].head(&[6], 100);
+        let walkers = variant([0, 0, 0], 300).new_cell_state(around.iter());
+        assert_eq!(walkers.status, Status::Human);
+        let tanks = variant([0, 100, 0], 300).new_cell_state(around.iter());
+        assert_eq!(tanks.status, Status::Zombie);
+        // They still count as heads, fewer of them went down
+        assert!(tanks.ledger.zombie_deaths < walkers.ledger.zombie_deaths);
+    }
+
+    #[test]
+    fn screamers_give_off_more_smell() {
+        let config = SimulationConfig::default();
+        let smell = |variants| {
+            variant(variants, 10)
+                .new_cell_state(surroundings().iter())
+                .smell_zombie
+        };
+        assert!(smell([0, 0, 0]) > 0);
+        assert_eq!(
+            smell([0, 0, 100]),
+            smell([0, 0, 0]) * config.screamer_smell_percent / 100
+        );
+    }
+
+    /// 100 well fed humans of faction 1 at (1, 1), after `arriving` of `faction` (lineage 9)
+    /// come in from the east.
+    fn joined_by(faction: u8, arriving: i32) -> ZombieState {
+        let mut around = surroundings();
+        around[4] = ZombieState {
+            status: Status::Human,
+            population: arriving,
+            faction,
+            lineage: 9,
+            ..around[4].clone()
+        };
+        around[4].head(&[6], 100);
+        ZombieState::builder(IVec2::ONE)
+            .status(Status::Human)
+            .population(100)
+            .faction(1)
+            .lineage(7)
+            .food(SimulationConfig::default().food_capacity)
+            .build()
+            .unwrap()
+            .new_cell_state(around.iter())
+    }
+
+    #[test]
+    fn rival_factions_fight_for_the_cell_instead_of_joining() {
+        let allies = joined_by(1, 60);
+        assert_eq!((allies.population, allies.faction), (160, 1));
+        assert_eq!(allies.ledger.human_deaths, 0);
+
+        let held = joined_by(2, 60);
+        assert_eq!((held.population, held.faction, held.lineage), (40, 1, 7));
+        assert_eq!(held.ledger.human_deaths, 120);
+
+        let taken = joined_by(2, 150);
+        assert_eq!(taken.status, Status::Human);
+        assert_eq!((taken.population, taken.faction, taken.lineage), (50, 2, 9));
+        assert_eq!(taken.ledger.human_deaths, 200);
+    }
+}